@@ -1,45 +1,264 @@
 // src-tauri/src/commands/chat.rs
 
+use std::collections::HashMap;
 use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
-use rusqlite::params;
-use tauri::{AppHandle, Emitter, State};
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use serde_json::Map;
+use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::db::{open_db, unix_ms};
+use crate::commands::server::server_status;
+use crate::db::{
+    self, activate_variant, delete_draft, delete_empty_chats, edit_user_message, load_draft, open_db,
+    unix_ms,
+};
+use crate::events::CHATS_CHANGED;
+use crate::http::http_client;
 use crate::state::LlamaServerManager;
 use crate::types::{
-    ChatListItem, ChatMessageRow, DeleteChatArgs, GenerateTitleArgs, RenameChatArgs,
-    OpenAIContent, OpenAIMessage, OpenAINonStreamResponse, OpenAIRequest,
+    ArchiveChatArgs, CancelGenerationArgs, ChatDraft, ChatListItem, ChatMessageRow,
+    ChatMessagesPage, ChatSystemPromptArgs, ChatTagArgs, DeleteChatArgs, DraftArgs,
+    DuplicateChatArgs, EditMessageArgs, GenerateTitleArgs, ListChatsArgs, NewChatArgs,
+    OpenAIContent, OpenAIMessage, OpenAINonStreamResponse, OpenAIRequest, PinChatArgs,
+    RenameChatArgs, SelectVariantArgs, SetChatSystemPromptArgs,
 };
 
 #[tauri::command]
 pub fn model_status(state: State<'_, LlamaServerManager>) -> Result<bool, String> {
-    Ok(state.is_ready.load(Ordering::SeqCst))
+    Ok(server_status(&state)?.is_ready)
 }
 
+/// Create a chat and return its id. With `deferred`, only a provisional id is
+/// returned; the row is created when the first message is stored (see
+/// `insert_message`), so abandoned chats never reach the database.
 #[tauri::command]
-pub fn new_chat(app: AppHandle, state: State<'_, LlamaServerManager>) -> Result<String, String> {
+pub fn new_chat(
+    args: Option<NewChatArgs>,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<String, String> {
     let chat_id = uuid::Uuid::new_v4().to_string();
+    if args.map_or(false, |a| a.deferred) {
+        return Ok(chat_id);
+    }
+
     let now = unix_ms();
+    let model_id = state.current_model_id.lock().map_err(|e| e.to_string())?.clone();
 
     let conn = open_db(&state.db_path)?;
     conn.execute(
-        "INSERT INTO conversations (id, title, summary, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![chat_id, "New chat", "", now, now],
+        "INSERT INTO conversations (id, title, summary, model_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![chat_id, "New chat", "", model_id, now, now],
     )
     .map_err(|e| e.to_string())?;
 
-    let _ = app.emit("chats:changed", ());
+    let _ = app.emit(CHATS_CHANGED, ());
     Ok(chat_id)
 }
 
+/// Pinned chats first, then the most recently updated. Archived chats are
+/// left out unless `include_archived` is set; `tag_filter` keeps only chats
+/// with that tag.
+#[tauri::command]
+pub fn list_chats(
+    args: Option<ListChatsArgs>,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Vec<ChatListItem>, String> {
+    let args = args.unwrap_or_default();
+    let conn = open_db(&state.db_path)?;
+
+    let mut filter = if args.include_archived { "1 = 1" } else { "c.archived = 0" }.to_string();
+    let tag = args.tag_filter.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if tag.is_some() {
+        filter.push_str(
+            " AND EXISTS (SELECT 1 FROM tags t WHERE t.conversation_id = c.id AND t.tag = ?1)",
+        );
+    }
+
+    match tag {
+        Some(tag) => query_chat_list(&conn, &filter, &[&tag as &dyn ToSql]),
+        None => query_chat_list(&conn, &filter, &[]),
+    }
+}
+
+#[tauri::command]
+pub fn list_archived_chats(state: State<'_, LlamaServerManager>) -> Result<Vec<ChatListItem>, String> {
+    let conn = open_db(&state.db_path)?;
+    query_chat_list(&conn, "c.archived = 1", &[])
+}
+
+/// Tag a chat, e.g. with a project name. Tags are case-insensitive.
+#[tauri::command]
+pub fn add_tag(
+    args: ChatTagArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    let tag = args.tag.trim();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+
+    let conn = open_db(&state.db_path)?;
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM conversations WHERE id = ?1",
+            params![args.chat_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err(format!("Chat {} not found", args.chat_id));
+    }
+
+    conn.execute(
+        "INSERT OR IGNORE INTO tags (conversation_id, tag) VALUES (?1, ?2)",
+        params![args.chat_id, tag],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let _ = app.emit(CHATS_CHANGED, ());
+    Ok(())
+}
+
 #[tauri::command]
-pub fn list_chats(state: State<'_, LlamaServerManager>) -> Result<Vec<ChatListItem>, String> {
+pub fn remove_tag(
+    args: ChatTagArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
     let conn = open_db(&state.db_path)?;
+    conn.execute(
+        "DELETE FROM tags WHERE conversation_id = ?1 AND tag = ?2",
+        params![args.chat_id, args.tag.trim()],
+    )
+    .map_err(|e| e.to_string())?;
 
+    let _ = app.emit(CHATS_CHANGED, ());
+    Ok(())
+}
+
+/// Every tag in use, alphabetically.
+#[tauri::command]
+pub fn list_tags(state: State<'_, LlamaServerManager>) -> Result<Vec<String>, String> {
+    let conn = open_db(&state.db_path)?;
     let mut stmt = conn
-        .prepare(
+        .prepare("SELECT DISTINCT tag FROM tags ORDER BY tag COLLATE NOCASE")
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows {
+        out.push(r.map_err(|e| e.to_string())?);
+    }
+    Ok(out)
+}
+
+/// Hide a chat from the list without deleting it.
+#[tauri::command]
+pub fn archive_chat(
+    args: ArchiveChatArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    set_chat_flag(&app, &state, &args.chat_id, "archived", true)
+}
+
+#[tauri::command]
+pub fn unarchive_chat(
+    args: ArchiveChatArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    set_chat_flag(&app, &state, &args.chat_id, "archived", false)
+}
+
+/// Keep a chat at the top of the list.
+#[tauri::command]
+pub fn pin_chat(
+    args: PinChatArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    set_chat_flag(&app, &state, &args.chat_id, "pinned", true)
+}
+
+#[tauri::command]
+pub fn unpin_chat(
+    args: PinChatArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    set_chat_flag(&app, &state, &args.chat_id, "pinned", false)
+}
+
+/// The chat's own system prompt, or None when it uses the global one.
+#[tauri::command]
+pub fn get_chat_system_prompt(
+    args: ChatSystemPromptArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Option<String>, String> {
+    let conn = open_db(&state.db_path)?;
+    db::load_chat_system_prompt(&conn, &args.chat_id)
+}
+
+/// Override the system prompt for one chat; None or blank restores the
+/// global prompt from settings.
+#[tauri::command]
+pub fn set_chat_system_prompt(
+    args: SetChatSystemPromptArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    let prompt = args
+        .system_prompt
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty());
+
+    let conn = open_db(&state.db_path)?;
+    if !db::set_chat_system_prompt(&conn, &args.chat_id, prompt)? {
+        return Err(format!("Chat {} not found", args.chat_id));
+    }
+    Ok(())
+}
+
+/// Set a boolean column of `conversations`; `column` is always a literal.
+fn set_chat_flag(
+    app: &AppHandle,
+    state: &LlamaServerManager,
+    chat_id: &str,
+    column: &str,
+    value: bool,
+) -> Result<(), String> {
+    let conn = open_db(&state.db_path)?;
+    let changed = conn
+        .execute(
+            &format!("UPDATE conversations SET {} = ?1 WHERE id = ?2", column),
+            params![value, chat_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err(format!("Chat {} not found", chat_id));
+    }
+
+    let _ = app.emit(CHATS_CHANGED, ());
+    Ok(())
+}
+
+/// Chats matching `filter` (a condition on `c` using `filter_params`), pinned
+/// first, then newest, with their tags.
+fn query_chat_list(
+    conn: &Connection,
+    filter: &str,
+    filter_params: &[&dyn ToSql],
+) -> Result<Vec<ChatListItem>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
             r#"
             SELECT
                 c.id,
@@ -48,25 +267,34 @@ pub fn list_chats(state: State<'_, LlamaServerManager>) -> Result<Vec<ChatListIt
                 COALESCE(
                     (SELECT substr(m.content, 1, 120)
                      FROM messages m
-                     WHERE m.conversation_id = c.id
+                     WHERE m.conversation_id = c.id AND m.is_active = 1
                      ORDER BY m.created_at DESC
                      LIMIT 1),
                     ''
-                ) AS preview
+                ) AS preview,
+                c.archived,
+                c.pinned,
+                c.model_id
             FROM conversations c
-            ORDER BY c.updated_at DESC
+            WHERE {}
+            ORDER BY c.pinned DESC, c.updated_at DESC
             LIMIT 100
             "#,
-        )
+            filter
+        ))
         .map_err(|e| e.to_string())?;
 
     let rows = stmt
-        .query_map([], |row| {
+        .query_map(filter_params, |row| {
             Ok(ChatListItem {
                 id: row.get(0)?,
                 title: row.get(1)?,
                 updated_at: row.get(2)?,
                 preview: row.get(3)?,
+                archived: row.get(4)?,
+                pinned: row.get(5)?,
+                tags: Vec::new(),
+                model_id: row.get(6)?,
             })
         })
         .map_err(|e| e.to_string())?;
@@ -75,29 +303,72 @@ pub fn list_chats(state: State<'_, LlamaServerManager>) -> Result<Vec<ChatListIt
     for r in rows {
         out.push(r.map_err(|e| e.to_string())?);
     }
+
+    let mut tags = load_chat_tags(conn)?;
+    for chat in out.iter_mut() {
+        chat.tags = tags.remove(&chat.id).unwrap_or_default();
+    }
     Ok(out)
 }
 
+/// Tags of every chat, keyed by chat id, each list sorted.
+fn load_chat_tags(conn: &Connection) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT conversation_id, tag FROM tags ORDER BY tag COLLATE NOCASE")
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for r in rows {
+        let (chat_id, tag) = r.map_err(|e| e.to_string())?;
+        tags.entry(chat_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+/// A page of a chat's messages, oldest first. Pass the `created_at` and `id`
+/// of the first message of the previous page to fetch the ones before it;
+/// the id breaks ties between messages sharing a timestamp.
 #[tauri::command]
 pub fn get_chat_messages(
     chat_id: String,
+    limit: Option<u32>,
+    before_created_at: Option<i64>,
+    before_id: Option<String>,
     state: State<'_, LlamaServerManager>,
-) -> Result<Vec<ChatMessageRow>, String> {
+) -> Result<ChatMessagesPage, String> {
+    // Newest first so LIMIT keeps the latest page; one extra row tells us
+    // whether older messages remain. -1 means no limit to SQLite.
+    let fetch_limit = limit.map_or(-1, |l| i64::from(l) + 1);
+
     let conn = open_db(&state.db_path)?;
 
     let mut stmt = conn
         .prepare(
             r#"
-            SELECT id, role, content, thinking, images, created_at, duration_ms
-            FROM messages
-            WHERE conversation_id = ?1
-            ORDER BY created_at ASC
+            SELECT
+                m.id, m.role, m.content, m.thinking, m.images, m.created_at, m.duration_ms,
+                m.response_group_id, m.variant_index, m.is_active,
+                CASE WHEN m.response_group_id IS NULL THEN 1 ELSE
+                    (SELECT COUNT(*) FROM messages v WHERE v.response_group_id = m.response_group_id)
+                END AS variant_count,
+                m.prompt_tokens, m.completion_tokens, m.truncated
+            FROM messages m
+            WHERE m.conversation_id = ?1
+              AND (?2 IS NULL OR m.created_at < ?2
+                   OR (m.created_at = ?2
+                       AND m.rowid < (SELECT rowid FROM messages WHERE id = ?4)))
+            ORDER BY m.created_at DESC, m.rowid DESC
+            LIMIT ?3
             "#,
         )
         .map_err(|e| e.to_string())?;
 
     let rows = stmt
-        .query_map([chat_id], |row| {
+        .query_map(params![chat_id, before_created_at, fetch_limit, before_id], |row| {
             let images_json: String = row.get(4)?;
             let images: Vec<String> =
                 serde_json::from_str(&images_json).unwrap_or_else(|_| Vec::new());
@@ -110,28 +381,213 @@ pub fn get_chat_messages(
                 images,
                 created_at: row.get(5)?,
                 duration_ms: row.get(6)?,
+                response_group_id: row.get(7)?,
+                variant_index: row.get(8)?,
+                is_active: row.get(9)?,
+                variant_count: row.get(10)?,
+                prompt_tokens: row.get(11)?,
+                completion_tokens: row.get(12)?,
+                truncated: row.get(13)?,
             })
         })
         .map_err(|e| e.to_string())?;
 
-    let mut out = Vec::new();
+    let mut messages = Vec::new();
     for r in rows {
-        out.push(r.map_err(|e| e.to_string())?);
+        messages.push(r.map_err(|e| e.to_string())?);
     }
-    Ok(out)
+
+    let has_more = limit.map_or(false, |l| messages.len() > l as usize);
+    if has_more {
+        messages.pop();
+    }
+    messages.reverse();
+
+    let model_id = conn
+        .query_row(
+            "SELECT model_id FROM conversations WHERE id = ?1",
+            params![chat_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+
+    Ok(ChatMessagesPage {
+        messages,
+        has_more,
+        model_id,
+    })
 }
 
+/// Choose which regenerated variant of a response is shown as current and
+/// included in the context of future turns.
 #[tauri::command]
-pub fn rename_chat(args: RenameChatArgs, state: State<'_, LlamaServerManager>) -> Result<(), String> {
+pub fn select_variant(
+    args: SelectVariantArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    let conn = open_db(&state.db_path)?;
+    activate_variant(&conn, &args.message_id)?;
+
+    let _ = app.emit(CHATS_CHANGED, ());
+    Ok(())
+}
+
+/// Fix a sent prompt: update the user message and delete every message after
+/// it. Follow up with `regenerate_response`, which streams a fresh reply when
+/// the chat ends with a user message.
+#[tauri::command]
+pub fn edit_message(
+    args: EditMessageArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    if args.content.trim().is_empty() {
+        return Err("Message cannot be empty".to_string());
+    }
+
+    let mut conn = open_db(&state.db_path)?;
+    let chat_id = edit_user_message(&mut conn, &args.message_id, &args.content)?;
+    println!("[edit_message] Edited {} in {}", args.message_id, chat_id);
+
+    let _ = app.emit(CHATS_CHANGED, ());
+    Ok(())
+}
+
+/// Branch a chat: copy it, up to and including `up_to_message_id`, into a
+/// new conversation and return the new chat id.
+#[tauri::command]
+pub fn duplicate_chat(
+    args: DuplicateChatArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<String, String> {
+    let mut conn = open_db(&state.db_path)?;
+    let new_id = db::duplicate_chat(&mut conn, &args.chat_id, args.up_to_message_id.as_deref())?;
+    println!("[duplicate_chat] Copied {} to {}", args.chat_id, new_id);
+
+    let _ = app.emit(CHATS_CHANGED, ());
+    Ok(new_id)
+}
+
+#[tauri::command]
+pub fn rename_chat(
+    args: RenameChatArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    // Live edits: only the last title within the debounce window is written
+    let generation = bump_rename_generation(&state, &args.chat_id)?;
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(RENAME_DEBOUNCE).await;
+
+        let state = app.state::<LlamaServerManager>();
+        // The latest edit writes and clears the chat's entry
+        let is_latest = state
+            .rename_generations
+            .lock()
+            .map(|mut generations| {
+                let latest = generations.get(&args.chat_id) == Some(&generation);
+                if latest {
+                    generations.remove(&args.chat_id);
+                }
+                latest
+            })
+            .unwrap_or(false);
+        if !is_latest {
+            return;
+        }
+
+        if let Err(e) = commit_rename(&app, &state, &args.chat_id, &args.title) {
+            eprintln!("[rename_chat] {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Commit a rename immediately, dropping any pending debounced write.
+#[tauri::command]
+pub fn rename_chat_final(
+    args: RenameChatArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    forget_pending_rename(&state, &args.chat_id)?;
+    commit_rename(&app, &state, &args.chat_id, &args.title)
+}
+
+/// How long `rename_chat` waits for further edits before writing.
+const RENAME_DEBOUNCE: Duration = Duration::from_millis(500);
+
+fn bump_rename_generation(state: &LlamaServerManager, chat_id: &str) -> Result<u64, String> {
+    let generation = state.rename_counter.fetch_add(1, Ordering::SeqCst) + 1;
+    let mut generations = state.rename_generations.lock().map_err(|e| e.to_string())?;
+    generations.insert(chat_id.to_string(), generation);
+    Ok(generation)
+}
+
+/// Drop the chat's pending debounced rename, if any.
+fn forget_pending_rename(state: &LlamaServerManager, chat_id: &str) -> Result<(), String> {
+    let mut generations = state.rename_generations.lock().map_err(|e| e.to_string())?;
+    generations.remove(chat_id);
+    Ok(())
+}
+
+fn commit_rename(
+    app: &AppHandle,
+    state: &LlamaServerManager,
+    chat_id: &str,
+    title: &str,
+) -> Result<(), String> {
     let conn = open_db(&state.db_path)?;
     conn.execute(
         "UPDATE conversations SET title = ?1, updated_at = ?2 WHERE id = ?3",
-        params![args.title, unix_ms(), args.chat_id],
+        params![title, unix_ms(), chat_id],
     )
     .map_err(|e| e.to_string())?;
+
+    let _ = app.emit(CHATS_CHANGED, ());
     Ok(())
 }
 
+/// A wedged server must not hold a title generation (and its lock) forever.
+const TITLE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest first message sent to the title model, in bytes
+const TITLE_PROMPT_MAX_BYTES: usize = 300;
+
+/// The longest prefix of `text` of at most `max_bytes` bytes that ends on a
+/// char boundary, so multibyte text (emoji, CJK) can't cause a panic.
+fn truncate_at_char_boundary(text: &str, max_bytes: usize) -> &str {
+    let cut = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .take_while(|&i| i <= max_bytes)
+        .last()
+        .unwrap_or(0);
+    &text[..cut]
+}
+
+/// Removes a chat from `title_generations` when its generation ends.
+struct TitleGenerationGuard<'a> {
+    state: &'a LlamaServerManager,
+    chat_id: String,
+}
+
+impl Drop for TitleGenerationGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut chats) = self.state.title_generations.lock() {
+            chats.remove(&self.chat_id);
+        }
+    }
+}
+
+/// Generate a title from the chat's first message. Runs at most once per
+/// chat and never replaces a title the user already changed.
 #[tauri::command]
 pub async fn generate_chat_title(
     args: GenerateTitleArgs,
@@ -146,6 +602,35 @@ pub async fn generate_chat_title(
         return Ok(());
     }
 
+    let needs_title: bool = {
+        let conn = open_db(&state.db_path)?;
+        conn.query_row(
+            "SELECT title_generated = 0 AND title = 'New chat' FROM conversations WHERE id = ?1",
+            params![chat_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false)
+    };
+    if !needs_title {
+        return Ok(());
+    }
+
+    // Two quick messages may both ask for a title; only the first one runs
+    if !state
+        .title_generations
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(chat_id.clone())
+    {
+        return Ok(());
+    }
+    let _guard = TitleGenerationGuard {
+        state: &state,
+        chat_id: chat_id.clone(),
+    };
+
     // Get the first user message from this chat
     let first_message = {
         let conn = open_db(&state.db_path)?;
@@ -172,18 +657,33 @@ pub async fn generate_chat_title(
         None => return Ok(()), // No user message yet, nothing to do
     };
 
+    let (temperature, top_p, stop) = {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        (
+            settings.behavior.temperature,
+            settings.behavior.top_p,
+            settings.behavior.stop.clone(),
+        )
+    };
+
     // Truncate message if too long (for efficiency)
-    let truncated_msg = if first_message.len() > 300 {
-        format!("{}...", &first_message[..300])
+    let truncated_msg = if first_message.len() > TITLE_PROMPT_MAX_BYTES {
+        format!("{}...", truncate_at_char_boundary(&first_message, TITLE_PROMPT_MAX_BYTES))
     } else {
         first_message
     };
 
     // Use LLM to generate a concise title
-    let client = reqwest::Client::new();
+    let client = match http_client(&state) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("[generate_chat_title] Failed to build HTTP client: {}", e);
+            return Ok(());
+        }
+    };
 
     let request_body = OpenAIRequest {
-        model: "default".to_string(),
+        model: state.served_model_name(),
         messages: vec![
             OpenAIMessage {
                 role: "system".to_string(),
@@ -198,11 +698,18 @@ pub async fn generate_chat_title(
         ],
         stream: false,
         max_tokens: 30,
+        temperature,
+        top_p,
+        stop,
+        stream_options: None,
+        cache_prompt: None,
+        extra: Map::new(),
     };
 
     let response = match client
-        .post(format!("{}/v1/chat/completions", state.server_url))
+        .post(format!("{}/v1/chat/completions", state.server_url()))
         .header("Content-Type", "application/json")
+        .timeout(TITLE_TIMEOUT)
         .json(&request_body)
         .send()
         .await
@@ -247,50 +754,170 @@ pub async fn generate_chat_title(
         .take(80)
         .collect::<String>();
 
-    let final_title = if final_title.is_empty() {
-        "New chat".to_string()
-    } else {
-        final_title
-    };
+    // Nothing usable: leave the chat untitled so a later call can retry
+    if final_title.is_empty() || final_title == "New chat" {
+        eprintln!("[generate_chat_title] Empty title generated, keeping \"New chat\"");
+        return Ok(());
+    }
 
     eprintln!("[generate_chat_title] Generated title: {:?}", final_title);
 
-    // Update the chat title in the database
+    // Update the chat title in the database, unless it was renamed meanwhile
     {
         let conn = open_db(&state.db_path)?;
         conn.execute(
-            "UPDATE conversations SET title = ?1, updated_at = ?2 WHERE id = ?3",
+            "UPDATE conversations SET title = ?1, title_generated = 1, updated_at = ?2
+             WHERE id = ?3 AND title = 'New chat'",
             params![final_title, unix_ms(), chat_id],
         )
         .map_err(|e| e.to_string())?;
     }
 
     // Notify frontend that chats have changed
-    let _ = app.emit("chats:changed", ());
+    let _ = app.emit(CHATS_CHANGED, ());
 
     Ok(())
 }
 
 #[tauri::command]
 pub fn delete_chat(args: DeleteChatArgs, app: AppHandle, state: State<'_, LlamaServerManager>) -> Result<(), String> {
-    let conn = open_db(&state.db_path)?;
-    conn.execute(
-        "DELETE FROM messages WHERE conversation_id = ?1",
-        params![args.chat_id.clone()],
-    )
-    .map_err(|e| e.to_string())?;
-    conn.execute(
+    let mut conn = open_db(&state.db_path)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    // ON DELETE CASCADE covers these when foreign keys are on; deleting them
+    // explicitly doesn't rely on the pragma
+    for table in ["messages", "drafts", "snapshots", "tags"] {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE conversation_id = ?1", table),
+            params![args.chat_id.clone()],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.execute(
         "DELETE FROM conversations WHERE id = ?1",
         params![args.chat_id],
     )
     .map_err(|e| e.to_string())?;
 
-    let _ = app.emit("chats:changed", ());
+    tx.commit().map_err(|e| e.to_string())?;
+    forget_pending_rename(&state, &args.chat_id)?;
+
+    let _ = app.emit(CHATS_CHANGED, ());
     Ok(())
 }
 
+/// Remove empty "New chat" conversations, returning how many were deleted.
+#[tauri::command]
+pub fn cleanup_empty_chats(app: AppHandle, state: State<'_, LlamaServerManager>) -> Result<usize, String> {
+    let mut conn = open_db(&state.db_path)?;
+    let removed = delete_empty_chats(&mut conn)?;
+
+    println!("[chat] Removed {} empty chats", removed);
+    if removed > 0 {
+        let _ = app.emit(CHATS_CHANGED, ());
+    }
+    Ok(removed)
+}
+
 #[tauri::command]
-pub fn cancel_generation(state: State<'_, LlamaServerManager>) -> Result<(), String> {
-    state.is_cancelled.store(true, Ordering::SeqCst);
+pub fn cancel_generation(
+    args: CancelGenerationArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    cancel_chat_generation(&state, &args.chat_id)?;
     Ok(())
 }
+
+/// Set the cancel token of the chat's in-flight generation, if there is one.
+fn cancel_chat_generation(state: &LlamaServerManager, chat_id: &str) -> Result<bool, String> {
+    let generations = state.generations.lock().map_err(|e| e.to_string())?;
+    match generations.get(chat_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// How long `cancel_to_draft` waits for the stream to notice the cancellation.
+const DRAFT_WAIT: Duration = Duration::from_secs(5);
+
+/// Stop the chat's generation and keep what was produced so far as an
+/// editable draft for the chat, rather than a committed assistant message.
+#[tauri::command]
+pub async fn cancel_to_draft(
+    args: DraftArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<ChatDraft, String> {
+    let chat_id = args.chat_id;
+
+    {
+        let mut requests = state.draft_requests.lock().map_err(|e| e.to_string())?;
+        requests.insert(chat_id.clone());
+    }
+    if !cancel_chat_generation(&state, &chat_id)? {
+        let mut requests = state.draft_requests.lock().map_err(|e| e.to_string())?;
+        requests.remove(&chat_id);
+        return Err("No generation in progress for this chat".to_string());
+    }
+
+    // The stream removes the request once the draft is stored
+    let start = Instant::now();
+    loop {
+        let pending = {
+            let requests = state.draft_requests.lock().map_err(|e| e.to_string())?;
+            requests.contains(&chat_id)
+        };
+        if !pending {
+            break;
+        }
+        if start.elapsed() > DRAFT_WAIT {
+            // The request stays, so a stream that stops late still saves the
+            // draft; it is dropped with the generation either way
+            return Err("Timed out waiting for the draft to be saved".to_string());
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    let conn = open_db(&state.db_path)?;
+    load_draft(&conn, &chat_id)?.ok_or_else(|| "Draft was not saved".to_string())
+}
+
+#[tauri::command]
+pub fn get_draft(
+    args: DraftArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Option<ChatDraft>, String> {
+    let conn = open_db(&state.db_path)?;
+    load_draft(&conn, &args.chat_id)
+}
+
+#[tauri::command]
+pub fn clear_draft(args: DraftArgs, state: State<'_, LlamaServerManager>) -> Result<(), String> {
+    let conn = open_db(&state.db_path)?;
+    delete_draft(&conn, &args.chat_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncation_past_the_limit_stays_on_a_char_boundary() {
+        // 4-byte emoji and 3-byte CJK characters straddle byte 300
+        let text = format!("{}{}", "a".repeat(298), "😀漢字".repeat(50));
+        let truncated = truncate_at_char_boundary(&text, TITLE_PROMPT_MAX_BYTES);
+        assert_eq!(truncated, "a".repeat(298));
+
+        let cjk = "漢".repeat(200);
+        let truncated = truncate_at_char_boundary(&cjk, TITLE_PROMPT_MAX_BYTES);
+        assert_eq!(truncated.len(), 300);
+        assert!(truncated.chars().all(|c| c == '漢'));
+    }
+
+    #[test]
+    fn short_text_is_kept_whole() {
+        assert_eq!(truncate_at_char_boundary("héllo", TITLE_PROMPT_MAX_BYTES), "héllo");
+    }
+}
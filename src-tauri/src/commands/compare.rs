@@ -0,0 +1,192 @@
+// src-tauri/src/commands/compare.rs
+
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::commands::model::{resolve_model_paths, restart_server_with};
+use crate::commands::streaming::build_openai_messages;
+use crate::events::COMPARE_PROGRESS;
+use crate::http::http_client;
+use crate::state::LlamaServerManager;
+use crate::think::ThinkTagParser;
+use crate::types::{
+    ChatMsg, CompareModelsArgs, CompareProgressPayload, ModelComparison, OpenAINonStreamResponse,
+    OpenAIRequest,
+};
+
+/// Run one prompt against two models and return both answers side by side.
+///
+/// Only one llama-server runs at a time, so the models are loaded one after
+/// the other and the previously loaded model is restored afterwards. Nothing
+/// is written to the chat history. A model that fails to load or answer gets
+/// its `error` set instead of aborting the whole comparison.
+#[tauri::command]
+pub async fn compare_models(
+    args: CompareModelsArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Vec<ModelComparison>, String> {
+    if args.prompt.trim().is_empty() {
+        return Err("Prompt is empty".to_string());
+    }
+    // Fail early on unknown or missing models rather than halfway through
+    for model_id in [&args.model_a, &args.model_b] {
+        resolve_model_paths(&app, &state, model_id)?;
+    }
+
+    let original_id = state.current_model_id.lock().map_err(|e| e.to_string())?.clone();
+    let original = match original_id {
+        Some(id) => {
+            let model_path = state.model_path.lock().map_err(|e| e.to_string())?.clone();
+            let mmproj_path = state.mmproj_path.lock().map_err(|e| e.to_string())?.clone();
+            Some((id, model_path, mmproj_path))
+        }
+        None => None,
+    };
+
+    let mut results = Vec::new();
+    for model_id in [args.model_a.clone(), args.model_b.clone()] {
+        let result = compare_one(&app, &state, &model_id, &args.prompt).await;
+        let comparison = match result {
+            Ok(comparison) => comparison,
+            Err(e) => {
+                eprintln!("[compare] {} failed: {}", model_id, e);
+                ModelComparison {
+                    model_id: model_id.clone(),
+                    content: String::new(),
+                    thinking: String::new(),
+                    load_ms: 0,
+                    latency_ms: 0,
+                    prompt_tokens: None,
+                    completion_tokens: None,
+                    tokens_per_second: None,
+                    error: Some(e),
+                }
+            }
+        };
+        let _ = app.emit(
+            COMPARE_PROGRESS,
+            CompareProgressPayload {
+                model_id,
+                status: if comparison.error.is_some() { "error" } else { "done" }.to_string(),
+                error: comparison.error.clone(),
+            },
+        );
+        results.push(comparison);
+    }
+
+    // Put back whatever was loaded before the comparison
+    if let Some((model_id, model_path, mmproj_path)) = original {
+        let current = state.current_model_id.lock().map_err(|e| e.to_string())?.clone();
+        let ready = state.is_ready.load(Ordering::SeqCst);
+        if current.as_deref() != Some(model_id.as_str()) || !ready {
+            restart_server_with(&app, &state, model_id, model_path, mmproj_path).await?;
+        }
+    }
+
+    Ok(results)
+}
+
+async fn compare_one(
+    app: &AppHandle,
+    state: &LlamaServerManager,
+    model_id: &str,
+    prompt: &str,
+) -> Result<ModelComparison, String> {
+    let progress = |status: &str| {
+        let _ = app.emit(
+            COMPARE_PROGRESS,
+            CompareProgressPayload {
+                model_id: model_id.to_string(),
+                status: status.to_string(),
+                error: None,
+            },
+        );
+    };
+
+    progress("loading");
+    let load_start = Instant::now();
+    let already_loaded = state.is_ready.load(Ordering::SeqCst)
+        && state.current_model_id.lock().map_err(|e| e.to_string())?.as_deref() == Some(model_id);
+    if !already_loaded {
+        let (model_path, mmproj_path) = resolve_model_paths(app, state, model_id)?;
+        restart_server_with(app, state, model_id.to_string(), model_path, mmproj_path).await?;
+    }
+    let load_ms = load_start.elapsed().as_millis() as u64;
+
+    let (system_prompt, behavior) = {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        (settings.defaults.system_prompt.clone(), settings.behavior.clone())
+    };
+
+    let history = [ChatMsg {
+        role: "user".to_string(),
+        content: prompt.to_string(),
+        thinking: String::new(),
+        images: Vec::new(),
+    }];
+    let request_body = OpenAIRequest {
+        model: state.served_model_name(),
+        messages: build_openai_messages(system_prompt, &history, &behavior),
+        stream: false,
+        max_tokens: behavior.max_tokens,
+        temperature: behavior.temperature,
+        top_p: behavior.top_p,
+        stop: behavior.stop.clone(),
+        stream_options: None,
+        cache_prompt: Some(false),
+        extra: behavior.request_extra_params(),
+    };
+
+    progress("generating");
+    let request_start = Instant::now();
+    let response = http_client(state)?
+        .post(format!("{}/v1/chat/completions", state.server_url()))
+        .header("Content-Type", "application/json")
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP error: {}", response.status()));
+    }
+
+    let body: OpenAINonStreamResponse = response.json().await.map_err(|e| e.to_string())?;
+    let latency_ms = request_start.elapsed().as_millis() as u64;
+
+    let message = body.choices.first().map(|c| &c.message);
+    let mut content = message.and_then(|m| m.content.clone()).unwrap_or_default();
+    let mut thinking = message
+        .and_then(|m| m.reasoning_content.clone())
+        .unwrap_or_default();
+
+    if behavior.parse_think_tags {
+        let mut parser = ThinkTagParser::new();
+        let (mut text, mut reasoning) = parser.feed(&content);
+        let (rest_text, rest_reasoning) = parser.finish();
+        text.push_str(&rest_text);
+        reasoning.push_str(&rest_reasoning);
+        content = text;
+        thinking.push_str(&reasoning);
+    }
+
+    let tokens_per_second = body
+        .usage
+        .filter(|_| latency_ms > 0)
+        .map(|u| u.completion_tokens as f64 / (latency_ms as f64 / 1000.0));
+
+    Ok(ModelComparison {
+        model_id: model_id.to_string(),
+        content,
+        thinking,
+        load_ms,
+        latency_ms,
+        prompt_tokens: body.usage.map(|u| u.prompt_tokens),
+        completion_tokens: body.usage.map(|u| u.completion_tokens),
+        tokens_per_second,
+        error: None,
+    })
+}
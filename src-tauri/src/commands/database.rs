@@ -0,0 +1,153 @@
+// src-tauri/src/commands/database.rs
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::{init_db, open_db, SCHEMA_VERSION};
+use crate::events::CHATS_CHANGED;
+use crate::state::LlamaServerManager;
+use crate::types::{DatabasePathArgs, DbBackupResult, DbFlushResult, DbVacuumResult};
+
+/// Checkpoint the WAL into the main database file and truncate it, so recent
+/// writes are in the main file (e.g. before copying it as a backup).
+#[tauri::command]
+pub fn flush_db(state: State<'_, LlamaServerManager>) -> Result<DbFlushResult, String> {
+    let conn = open_db(&state.db_path)?;
+    let busy = checkpoint(&conn)?;
+    drop(conn);
+
+    let result = DbFlushResult {
+        db_bytes: file_size(&state.db_path),
+        wal_bytes: file_size(&wal_path(&state.db_path)),
+        busy,
+    };
+    println!(
+        "[db] Checkpointed WAL (db: {} bytes, wal: {} bytes, busy: {})",
+        result.db_bytes, result.wal_bytes, result.busy
+    );
+    Ok(result)
+}
+
+/// Rebuild the database file to reclaim space left by deleted chats. The WAL
+/// is checkpointed before (so VACUUM sees every page) and after (VACUUM
+/// itself writes through the WAL).
+#[tauri::command]
+pub fn vacuum_database(state: State<'_, LlamaServerManager>) -> Result<DbVacuumResult, String> {
+    let wal_path = wal_path(&state.db_path);
+    let conn = open_db(&state.db_path)?;
+
+    checkpoint(&conn)?;
+    let before_bytes = file_size(&state.db_path) + file_size(&wal_path);
+
+    // Must not run inside a transaction; each command gets a fresh connection
+    conn.execute_batch("VACUUM").map_err(|e| e.to_string())?;
+    checkpoint(&conn)?;
+    drop(conn);
+
+    let result = DbVacuumResult {
+        before_bytes,
+        after_bytes: file_size(&state.db_path) + file_size(&wal_path),
+    };
+    println!(
+        "[db] Vacuumed: {} -> {} bytes",
+        result.before_bytes, result.after_bytes
+    );
+    Ok(result)
+}
+
+/// `PRAGMA wal_checkpoint(TRUNCATE)`; returns whether another connection kept
+/// it from completing.
+fn checkpoint(conn: &Connection) -> Result<bool, String> {
+    let busy: i64 = conn
+        .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    Ok(busy != 0)
+}
+
+fn wal_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_owned();
+    name.push("-wal");
+    PathBuf::from(name)
+}
+
+/// Copy the live database to `path` with SQLite's online backup API, which is
+/// consistent while the app keeps writing (a plain file copy could miss
+/// pages still in the WAL).
+#[tauri::command]
+pub fn export_database(
+    args: DatabasePathArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<DbBackupResult, String> {
+    let dest = PathBuf::from(&args.path);
+    if dest == state.db_path {
+        return Err("Cannot export the database onto itself".to_string());
+    }
+
+    let conn = open_db(&state.db_path)?;
+    conn.backup(DatabaseName::Main, &dest, None)
+        .map_err(|e| format!("Backup failed: {}", e))?;
+
+    let result = DbBackupResult {
+        path: args.path,
+        bytes: file_size(&dest),
+    };
+    println!("[db] Exported {} bytes to {}", result.bytes, result.path);
+    Ok(result)
+}
+
+/// Replace all chats with those in the backup at `path`. The backup must be
+/// an eigenAgent database no newer than this build; older ones are migrated.
+#[tauri::command]
+pub fn import_database(
+    args: DatabasePathArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    let source = PathBuf::from(&args.path);
+    validate_backup(&source)?;
+
+    let mut conn = open_db(&state.db_path)?;
+    conn.restore(DatabaseName::Main, &source, None::<fn(Progress)>)
+        .map_err(|e| format!("Restore failed: {}", e))?;
+    init_db(&conn)?;
+
+    println!("[db] Imported {}", source.display());
+    let _ = app.emit(CHATS_CHANGED, ());
+    Ok(())
+}
+
+/// Check that `path` is a database with our tables and a supported version.
+fn validate_backup(path: &Path) -> Result<(), String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Cannot open backup: {}", e))?;
+
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|_| "Not a SQLite database".to_string())?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "Backup is from a newer version of the app (schema {}, supported {})",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    let tables: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master
+             WHERE type = 'table' AND name IN ('conversations', 'messages')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if tables != 2 {
+        return Err("Not an eigenAgent chat database".to_string());
+    }
+    Ok(())
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
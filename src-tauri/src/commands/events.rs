@@ -0,0 +1,9 @@
+// src-tauri/src/commands/events.rs
+
+use crate::events::{EventInfo, EVENT_SCHEMA};
+
+/// Every event the backend emits, with its payload type, for frontend tooling.
+#[tauri::command]
+pub fn list_event_schema() -> Vec<EventInfo> {
+    EVENT_SCHEMA.to_vec()
+}
@@ -1,9 +1,23 @@
 // src-tauri/src/commands/mod.rs
 
 pub mod chat;
+pub mod compare;
+pub mod database;
+pub mod events;
 pub mod model;
+pub mod search;
+pub mod server;
+pub mod snapshot;
 pub mod streaming;
+pub mod system;
 
 pub use chat::*;
+pub use compare::*;
+pub use database::*;
+pub use events::*;
 pub use model::*;
+pub use search::*;
+pub use server::*;
+pub use snapshot::*;
 pub use streaming::*;
+pub use system::*;
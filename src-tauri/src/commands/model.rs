@@ -1,30 +1,90 @@
 // src-tauri/src/commands/model.rs
 
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+    Arc, Mutex,
 };
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use futures::StreamExt;
-use tauri::{AppHandle, Emitter, State};
-use tauri_plugin_shell::ShellExt;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::io::AsyncWriteExt;
 
+use crate::db::unix_ms;
+use crate::events::{
+    DOWNLOAD_COMPLETE, DOWNLOAD_ERROR, DOWNLOAD_PAUSED, DOWNLOAD_PROGRESS, DOWNLOAD_RESUMED,
+    DOWNLOAD_RETRY, MODELS_CHANGED, MODEL_LOADING, MODEL_NO_MODEL, MODEL_READY, MODEL_SWITCHING,
+    SETTINGS_CHANGED, VERIFY_PROGRESS,
+};
+use crate::http::{describe_http_status, describe_request_error, http_client};
 use crate::models::{
-    detect_legacy_model, get_model_dir, get_model_paths, is_model_downloaded,
-    load_or_create_catalog, scan_models_dir,
+    detect_legacy_model, find_catalog_conflicts, find_secondary_shards, get_model_dir,
+    get_model_paths, has_partial_download, is_download_paused, is_model_downloaded,
+    load_or_create_catalog, loaded_model_capabilities, merge_remote_catalog, parse_catalog,
+    partial_download_path, pause_marker_path, save_catalog, scan_models_dir, slugify,
+    unique_model_id, verify_model_files,
+};
+use crate::settings::save_settings;
+use crate::server::{
+    await_server_startup, report_cpu_backend_if_undetected, spawn_llama_server, stop_llama_server,
+    wait_for_server_ready, with_stderr_tail, STARTUP_CANCELLED,
 };
-use crate::server::wait_for_server_ready;
-use crate::state::{LlamaServerManager, SERVER_PORT};
+use crate::state::{DownloadControl, LlamaServerManager};
+use crate::system::{fits_in_ram, system_info};
 use crate::types::{
-    CancelDownloadArgs, DeleteModelArgs, DownloadModelArgs, DownloadProgressPayload,
-    ModelCapabilities, ModelFile, ModelInfo, ModelSwitchPayload, SwitchModelArgs,
+    AcceptLicenseArgs, CancelDownloadArgs, CatalogConflict, CheckCatalogConflictsArgs,
+    DeleteModelArgs, DownloadCompletePayload, DownloadErrorPayload, DownloadEstimate, DownloadInfo,
+    DownloadModelArgs, DownloadProgressPayload, DownloadResumedPayload, DownloadRetryPayload,
+    EstimateDownloadArgs, ExportModelStateArgs, ImportModelArgs, LocalFileState, LocalModelState,
+    ModelCapabilities, ModelCatalogEntry, ModelDetails, ModelDetailsArgs, ModelFile, ModelFiles,
+    ModelInfo, ModelStateExport, ModelSwitchPayload, ModelVerifyResult, ModelsByCapability,
+    SwitchModelArgs, VerifyProgressPayload,
 };
 
 #[tauri::command]
 pub fn list_models(app: AppHandle, state: State<'_, LlamaServerManager>) -> Result<Vec<ModelInfo>, String> {
-    let catalog = load_or_create_catalog(&app)?;
+    collect_models(&app, &state)
+}
+
+/// Group the same models `list_models` returns into capability buckets for
+/// a filtered model browser.
+#[tauri::command]
+pub fn list_models_by_capability(
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<ModelsByCapability, String> {
+    let mut grouped = ModelsByCapability::default();
+
+    for model in collect_models(&app, &state)? {
+        let caps = &model.capabilities;
+        if caps.vision {
+            grouped.vision.push(model.clone());
+        }
+        if caps.thinking {
+            grouped.thinking.push(model.clone());
+        }
+        if caps.tools {
+            grouped.tools.push(model.clone());
+        }
+        if !caps.vision && !caps.thinking && !caps.tools {
+            grouped.text_only.push(model);
+        }
+    }
+
+    Ok(grouped)
+}
+
+/// Catalog models plus the legacy flat-layout model, with download state.
+fn collect_models(app: &AppHandle, state: &LlamaServerManager) -> Result<Vec<ModelInfo>, String> {
+    let catalog = load_or_create_catalog(app)?;
+    let accepted_licenses = {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        settings.models.accepted_licenses.clone()
+    };
+    let available_memory = system_info().available_memory_bytes;
     let current_model_id = state.current_model_id.lock().map_err(|e| e.to_string())?;
     let downloading_progress = state.downloading_progress.lock().map_err(|e| e.to_string())?;
 
@@ -36,11 +96,13 @@ pub fn list_models(app: AppHandle, state: State<'_, LlamaServerManager>) -> Resu
                 "downloading".to_string()
             } else if is_model_downloaded(&state.models_dir, entry) {
                 "downloaded".to_string()
+            } else if has_partial_download(&state.models_dir, entry) {
+                partial_download_state(&state.models_dir, entry)
             } else {
                 "not_downloaded".to_string()
             };
 
-            let download_percent = downloading_progress.get(&entry.id).copied();
+            let download_percent = downloading_progress.get(&entry.id).copied().flatten();
 
             ModelInfo {
                 id: entry.id.clone(),
@@ -51,6 +113,11 @@ pub fn list_models(app: AppHandle, state: State<'_, LlamaServerManager>) -> Resu
                 download_status,
                 download_percent,
                 is_current: current_model_id.as_ref() == Some(&entry.id),
+                license: entry.license.clone(),
+                source_url: entry.source_url.clone(),
+                author: entry.author.clone(),
+                license_accepted: entry.license.is_none() || accepted_licenses.contains(&entry.id),
+                fits_in_ram: fits_in_ram(entry.files.total_bytes(), available_memory),
             }
         })
         .collect();
@@ -65,6 +132,11 @@ pub fn list_models(app: AppHandle, state: State<'_, LlamaServerManager>) -> Resu
                     .file_stem()
                     .map(|s| s.to_string_lossy().to_string())
                     .unwrap_or_else(|| "Legacy Model".to_string());
+                let size_bytes: u64 = std::iter::once(&model_path)
+                    .chain(mmproj_path.as_ref())
+                    .filter_map(|p| std::fs::metadata(p).ok())
+                    .map(|m| m.len())
+                    .sum();
 
                 models.insert(
                     0,
@@ -76,10 +148,16 @@ pub fn list_models(app: AppHandle, state: State<'_, LlamaServerManager>) -> Resu
                         capabilities: ModelCapabilities {
                             vision: mmproj_path.is_some(),
                             thinking: false,
+                            tools: false,
                         },
                         download_status: "downloaded".to_string(),
                         download_percent: None,
                         is_current: current_model_id.as_ref() == Some(&"legacy".to_string()),
+                        license: None,
+                        source_url: None,
+                        author: None,
+                        license_accepted: true,
+                        fits_in_ram: fits_in_ram(size_bytes, available_memory),
                     },
                 );
             }
@@ -89,6 +167,52 @@ pub fn list_models(app: AppHandle, state: State<'_, LlamaServerManager>) -> Resu
     Ok(models)
 }
 
+/// Model info plus license/source metadata and the files a download fetches.
+#[tauri::command]
+pub fn get_model_details(
+    args: ModelDetailsArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<ModelDetails, String> {
+    let info = collect_models(&app, &state)?
+        .into_iter()
+        .find(|m| m.id == args.model_id)
+        .ok_or_else(|| format!("Model {} not found", args.model_id))?;
+
+    let catalog = load_or_create_catalog(&app)?;
+    let files: Vec<ModelFile> = catalog
+        .models
+        .iter()
+        .find(|e| e.id == args.model_id)
+        .map(|e| e.files.all().into_iter().cloned().collect())
+        .unwrap_or_default();
+    let total_bytes = files.iter().map(|f| f.size_bytes).sum();
+
+    Ok(ModelDetails {
+        info,
+        files,
+        total_bytes,
+    })
+}
+
+/// Record that the user has seen and accepted a model's license.
+#[tauri::command]
+pub fn accept_model_license(
+    args: AcceptLicenseArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    let mut settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+    if !settings.models.accepted_licenses.contains(&args.model_id) {
+        settings.models.accepted_licenses.push(args.model_id);
+        save_settings(&settings)?;
+        // The settings UI saves whole objects; keep it from writing back a
+        // list without this acceptance
+        let _ = app.emit(SETTINGS_CHANGED, &*settings);
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn get_current_model(state: State<'_, LlamaServerManager>) -> Result<Option<String>, String> {
     let current = state.current_model_id.lock().map_err(|e| e.to_string())?;
@@ -105,7 +229,7 @@ pub async fn switch_model(
 
     // Emit switching status
     let _ = app.emit(
-        "model:switching",
+        MODEL_SWITCHING,
         ModelSwitchPayload {
             model_id: model_id.clone(),
             status: "stopping".to_string(),
@@ -113,34 +237,128 @@ pub async fn switch_model(
         },
     );
 
-    // Find the model in catalog
-    let catalog = load_or_create_catalog(&app)?;
+    let (model_path, mmproj_path) = resolve_model_paths(&app, &state, &model_id)?;
+    restart_server_with(&app, &state, model_id, model_path, mmproj_path).await
+}
 
-    let (model_path, mmproj_path) = if model_id == "legacy" {
-        // Handle legacy model
-        scan_models_dir(&state.models_dir).ok_or_else(|| "Legacy model not found".to_string())?
-    } else {
-        let entry = catalog
-            .models
-            .iter()
-            .find(|e| e.id == model_id)
-            .ok_or_else(|| format!("Model {} not found in catalog", model_id))?;
+/// Model (and mmproj) file paths for a downloaded catalog model or `legacy`.
+pub fn resolve_model_paths(
+    app: &AppHandle,
+    state: &LlamaServerManager,
+    model_id: &str,
+) -> Result<(PathBuf, Option<PathBuf>), String> {
+    if model_id == "legacy" {
+        return scan_models_dir(&state.models_dir)
+            .ok_or_else(|| "Legacy model not found".to_string());
+    }
 
-        get_model_paths(&state.models_dir, entry)
-            .ok_or_else(|| format!("Model {} is not downloaded", model_id))?
-    };
+    let catalog = load_or_create_catalog(app)?;
+    let entry = catalog
+        .models
+        .iter()
+        .find(|e| e.id == model_id)
+        .ok_or_else(|| format!("Model {} not found in catalog", model_id))?;
+
+    get_model_paths(&state.models_dir, entry)
+        .ok_or_else(|| format!("Model {} is not downloaded", model_id))
+}
+
+/// Capabilities of the current model; None when no model is selected.
+pub fn current_model_capabilities(state: &LlamaServerManager) -> Option<ModelCapabilities> {
+    state.current_capabilities.lock().ok()?.clone()
+}
+
+/// Stop llama-server and forget the current model, e.g. after a crash left
+/// `current_model_id` pointing at a model that is no longer loaded.
+#[tauri::command]
+pub fn clear_current_model(app: AppHandle, state: State<'_, LlamaServerManager>) -> Result<(), String> {
+    // Goes through stop_llama_server so the exit isn't taken for a crash
+    if stop_llama_server(&state)? {
+        println!("[model] Killed server while clearing current model");
+    }
 
-    // Kill current server
     {
-        let mut process_guard = state.process.lock().map_err(|e| e.to_string())?;
-        if let Some(child) = process_guard.take() {
-            let _ = child.kill();
-            println!("[model] Killed existing server");
-        }
+        let mut current = state.current_model_id.lock().map_err(|e| e.to_string())?;
+        *current = None;
     }
+    // Nothing may restart the cleared model from its old paths
+    *state.model_path.lock().map_err(|e| e.to_string())? = PathBuf::new();
+    *state.mmproj_path.lock().map_err(|e| e.to_string())? = None;
+    *state.current_capabilities.lock().map_err(|e| e.to_string())? = None;
+
+    let _ = app.emit(MODELS_CHANGED, ());
+    Ok(())
+}
 
-    // Mark as not ready
-    state.is_ready.store(false, Ordering::SeqCst);
+/// Abort a switch that is still waiting for llama-server to become healthy.
+/// The new server is killed and the previous model, if one was running, is
+/// started again.
+#[tauri::command]
+pub fn cancel_switch(state: State<'_, LlamaServerManager>) -> Result<(), String> {
+    if !state.switch_in_progress.load(Ordering::SeqCst) {
+        return Err("No model switch in progress".to_string());
+    }
+    state.switch_cancelled.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Restart llama-server with the currently loaded model, e.g. after it got
+/// into a bad state. Emits the same `model:switching` lifecycle as a switch.
+#[tauri::command]
+pub async fn reload_current_model(
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    let model_id = {
+        let current = state.current_model_id.lock().map_err(|e| e.to_string())?;
+        current
+            .clone()
+            .ok_or_else(|| "No model is currently loaded".to_string())?
+    };
+    let model_path = state.model_path.lock().map_err(|e| e.to_string())?.clone();
+    let mmproj_path = state.mmproj_path.lock().map_err(|e| e.to_string())?.clone();
+
+    if !model_path.exists() {
+        return Err(format!("Model file for {} no longer exists", model_id));
+    }
+
+    let _ = app.emit(
+        MODEL_SWITCHING,
+        ModelSwitchPayload {
+            model_id: model_id.clone(),
+            status: "stopping".to_string(),
+            error: None,
+        },
+    );
+
+    println!("[model] Reloading: {}", model_id);
+    restart_server_with(&app, &state, model_id, model_path, mmproj_path).await
+}
+
+/// Stop the running server (if any), record the new model as current and
+/// start llama-server with it, waiting until it reports healthy.
+pub async fn restart_server_with(
+    app: &AppHandle,
+    state: &LlamaServerManager,
+    model_id: String,
+    model_path: PathBuf,
+    mmproj_path: Option<PathBuf>,
+) -> Result<(), String> {
+    // Remember what was loaded so a cancelled switch can put it back
+    let previous_model_id = state.current_model_id.lock().map_err(|e| e.to_string())?.clone();
+    let previous_model_path = state.model_path.lock().map_err(|e| e.to_string())?.clone();
+    let previous_mmproj_path = state.mmproj_path.lock().map_err(|e| e.to_string())?.clone();
+    let previous_capabilities = state.current_capabilities.lock().map_err(|e| e.to_string())?.clone();
+    state.switch_cancelled.store(false, Ordering::SeqCst);
+    let _switching = SwitchInProgress::begin(state);
+    let capabilities = load_or_create_catalog(app)
+        .map(|catalog| loaded_model_capabilities(&catalog, &model_id, mmproj_path.is_some()))?;
+
+    // Kill current server and mark it not ready
+    let previous_was_running = stop_llama_server(state)?;
+    if previous_was_running {
+        println!("[model] Killed existing server");
+    }
 
     // Update model paths
     {
@@ -155,10 +373,11 @@ pub async fn switch_model(
         let mut current = state.current_model_id.lock().map_err(|e| e.to_string())?;
         *current = Some(model_id.clone());
     }
+    *state.current_capabilities.lock().map_err(|e| e.to_string())? = Some(capabilities);
 
     // Emit starting status
     let _ = app.emit(
-        "model:switching",
+        MODEL_SWITCHING,
         ModelSwitchPayload {
             model_id: model_id.clone(),
             status: "starting".to_string(),
@@ -167,111 +386,122 @@ pub async fn switch_model(
     );
 
     // Start new server
-    let shell = app.shell();
-    let mut cmd = shell
-        .sidecar("llama-server")
-        .map_err(|e| e.to_string())?;
-
-    // Get context length and max tokens from settings
-    let (ctx_size, max_tokens) = {
-        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
-        (
-            settings.behavior.context_length.to_string(),
-            settings.behavior.max_tokens.to_string(),
-        )
+    let spawned_at = match spawn_llama_server(app, state, &model_path, mmproj_path.as_deref()) {
+        Ok(spawned_at) => spawned_at,
+        Err(e) => {
+            let _ = app.emit(
+                MODEL_SWITCHING,
+                ModelSwitchPayload {
+                    model_id: model_id.clone(),
+                    status: "error".to_string(),
+                    error: Some(e.clone()),
+                },
+            );
+            return Err(e);
+        }
     };
 
-    cmd = cmd
-        .args(["-m", model_path.to_str().unwrap()])
-        .args(["--host", "127.0.0.1"])
-        .args(["--port", &SERVER_PORT.to_string()])
-        .args(["--ctx-size", &ctx_size])
-        .args(["--n-predict", &max_tokens]);
-
-    if let Some(ref mmproj) = mmproj_path {
-        cmd = cmd.args(["--mmproj", mmproj.to_str().unwrap()]);
-    }
-
-    match cmd.spawn() {
-        Ok((mut rx, child)) => {
-            // Store the child process
-            {
-                let mut guard = state.process.lock().map_err(|e| e.to_string())?;
-                *guard = Some(child);
+    // Wait for server to be ready
+    let server_url = state.server_url();
+    let wait = wait_for_server_ready(&server_url, 120, Some(&state.switch_cancelled)).await;
+    match wait {
+        Ok(()) => {
+            state.is_ready.store(true, Ordering::SeqCst);
+            report_cpu_backend_if_undetected(app);
+            let _ = app.emit(
+                MODEL_SWITCHING,
+                ModelSwitchPayload {
+                    model_id: model_id.clone(),
+                    status: "ready".to_string(),
+                    error: None,
+                },
+            );
+            let _ = app.emit(MODEL_READY, ());
+            println!("[llama-server] Ready with model: {}", model_id);
+        }
+        Err(e) if e == STARTUP_CANCELLED => {
+            stop_llama_server(state)?;
+            *state.model_path.lock().map_err(|e| e.to_string())? = previous_model_path.clone();
+            *state.mmproj_path.lock().map_err(|e| e.to_string())? = previous_mmproj_path.clone();
+            *state.current_model_id.lock().map_err(|e| e.to_string())? = previous_model_id;
+            *state.current_capabilities.lock().map_err(|e| e.to_string())? = previous_capabilities;
+            if previous_was_running {
+                let mmproj = previous_mmproj_path.as_deref();
+                restart_previous_model(app, state, &previous_model_path, mmproj)?;
             }
 
-            // Log server output in background
-            let app_clone = app.clone();
-            let model_id_clone = model_id.clone();
-            tauri::async_runtime::spawn(async move {
-                while let Some(event) = rx.recv().await {
-                    match event {
-                        tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
-                            println!("[llama-server] {}", String::from_utf8_lossy(&line));
-                        }
-                        tauri_plugin_shell::process::CommandEvent::Stderr(line) => {
-                            eprintln!("[llama-server] {}", String::from_utf8_lossy(&line));
-                        }
-                        tauri_plugin_shell::process::CommandEvent::Error(err) => {
-                            let _ = app_clone.emit(
-                                "model:switching",
-                                ModelSwitchPayload {
-                                    model_id: model_id_clone.clone(),
-                                    status: "error".to_string(),
-                                    error: Some(err),
-                                },
-                            );
-                        }
-                        _ => {}
-                    }
-                }
-            });
-
-            // Wait for server to be ready
-            let server_url = state.server_url.clone();
-            match wait_for_server_ready(&server_url, 120).await {
-                Ok(()) => {
-                    state.is_ready.store(true, Ordering::SeqCst);
-                    let _ = app.emit(
-                        "model:switching",
-                        ModelSwitchPayload {
-                            model_id: model_id.clone(),
-                            status: "ready".to_string(),
-                            error: None,
-                        },
-                    );
-                    let _ = app.emit("model:ready", ());
-                    println!("[llama-server] Ready with model: {}", model_id);
-                }
-                Err(e) => {
-                    let _ = app.emit(
-                        "model:switching",
-                        ModelSwitchPayload {
-                            model_id: model_id.clone(),
-                            status: "error".to_string(),
-                            error: Some(e.clone()),
-                        },
-                    );
-                    return Err(e);
-                }
-            }
+            println!("[model] Switch to {} cancelled", model_id);
+            let _ = app.emit(
+                MODEL_SWITCHING,
+                ModelSwitchPayload {
+                    model_id: model_id.clone(),
+                    status: "cancelled".to_string(),
+                    error: None,
+                },
+            );
+            return Err(e);
         }
         Err(e) => {
+            let e = with_stderr_tail(state, e, spawned_at);
             let _ = app.emit(
-                "model:switching",
+                MODEL_SWITCHING,
                 ModelSwitchPayload {
                     model_id: model_id.clone(),
                     status: "error".to_string(),
-                    error: Some(format!("Failed to spawn llama-server: {}", e)),
+                    error: Some(e.clone()),
                 },
             );
-            return Err(format!("Failed to spawn llama-server: {}", e));
+            return Err(e);
         }
     }
 
     Ok(())
 }
 
+/// Bring back the model a cancelled switch replaced, reporting the outcome
+/// with `model:ready`/`model:error` in the background. If it can't even be
+/// spawned, nothing is left marked as loaded.
+fn restart_previous_model(
+    app: &AppHandle,
+    state: &LlamaServerManager,
+    model_path: &Path,
+    mmproj_path: Option<&Path>,
+) -> Result<(), String> {
+    let _ = app.emit(MODEL_LOADING, ());
+    match spawn_llama_server(app, state, model_path, mmproj_path) {
+        Ok(spawned_at) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = await_server_startup(&app, spawned_at).await;
+            });
+        }
+        Err(e) => {
+            eprintln!("[model] Failed to restart previous model: {}", e);
+            *state.current_model_id.lock().map_err(|e| e.to_string())? = None;
+            *state.current_capabilities.lock().map_err(|e| e.to_string())? = None;
+            let _ = app.emit(MODEL_NO_MODEL, ());
+        }
+    }
+    Ok(())
+}
+
+/// Marks a model switch as in progress for `cancel_switch` until dropped,
+/// whichever way `restart_server_with` returns.
+struct SwitchInProgress<'a>(&'a AtomicBool);
+
+impl<'a> SwitchInProgress<'a> {
+    fn begin(state: &'a LlamaServerManager) -> Self {
+        state.switch_in_progress.store(true, Ordering::SeqCst);
+        Self(&state.switch_in_progress)
+    }
+}
+
+impl Drop for SwitchInProgress<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
 #[tauri::command]
 pub async fn download_model(
     args: DownloadModelArgs,
@@ -289,6 +519,18 @@ pub async fn download_model(
         .ok_or_else(|| format!("Model {} not found in catalog", model_id))?
         .clone();
 
+    if let Some(ref license) = entry.license {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        if !settings.models.accepted_licenses.contains(&model_id) {
+            return Err(format!(
+                "The {} license for {} must be accepted before downloading",
+                license, entry.name
+            ));
+        }
+    }
+
+    let client = http_client(&state)?;
+
     // Check if already downloading
     {
         let downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
@@ -297,154 +539,594 @@ pub async fn download_model(
         }
     }
 
-    // Create cancellation token
-    let cancel_token = Arc::new(AtomicBool::new(false));
+    // Create cancel/pause flags
+    let control = Arc::new(DownloadControl::default());
     {
         let mut downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
-        downloads.insert(model_id.clone(), cancel_token.clone());
+        downloads.insert(model_id.clone(), control.clone());
     }
 
     // Track progress
     {
         let mut progress = state.downloading_progress.lock().map_err(|e| e.to_string())?;
-        progress.insert(model_id.clone(), 0.0);
+        progress.insert(model_id.clone(), None);
     }
 
-    // Create model directory
+    // Create model directory; a resumed download is no longer paused
+    let started = Instant::now();
     let model_dir = get_model_dir(&state.models_dir, &model_id);
-    std::fs::create_dir_all(&model_dir).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(pause_marker_path(&model_dir));
+    let result = match std::fs::create_dir_all(&model_dir) {
+        Ok(()) => download_model_files(&app, &client, &entry, &model_dir, &control).await,
+        Err(e) => Err(e.to_string()),
+    };
 
-    // Calculate total bytes
-    let total_bytes = entry.files.model.size_bytes
-        + entry.files.mmproj.as_ref().map(|f| f.size_bytes).unwrap_or(0);
+    // Cleanup tracking
+    {
+        let mut downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
+        downloads.remove(&model_id);
+    }
+    {
+        let mut progress = state.downloading_progress.lock().map_err(|e| e.to_string())?;
+        progress.remove(&model_id);
+    }
 
-    // Download files
-    let files_to_download: Vec<&ModelFile> = {
-        let mut files = vec![&entry.files.model];
-        if let Some(ref mmproj) = entry.files.mmproj {
-            files.push(mmproj);
+    if control.cancelled.load(Ordering::SeqCst) {
+        let _ = std::fs::remove_dir_all(&model_dir);
+        return Err("Download cancelled".to_string());
+    }
+    if control.paused.load(Ordering::SeqCst) {
+        // Keep the .part files; their sizes are the offsets to resume from
+        if let Err(e) = std::fs::write(pause_marker_path(&model_dir), b"") {
+            eprintln!("[download] Failed to record pause of {}: {}", model_id, e);
+        }
+        let _ = app.emit(DOWNLOAD_PAUSED, model_id.clone());
+        println!("[download] Paused: {}", model_id);
+        return Ok(());
+    }
+    // Other failures keep partial files too, so a retry resumes
+    if let Err(e) = result {
+        eprintln!("[download] Failed: {}: {}", model_id, e);
+        let _ = app.emit(
+            DOWNLOAD_ERROR,
+            DownloadErrorPayload {
+                model_id: model_id.clone(),
+                error: e.clone(),
+            },
+        );
+        return Err(e);
+    }
+
+    // Emit completion
+    let total_bytes = control.downloaded_bytes.load(Ordering::SeqCst);
+    let _ = app.emit(
+        DOWNLOAD_COMPLETE,
+        DownloadCompletePayload {
+            model_id: model_id.clone(),
+            total_bytes,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        },
+    );
+    println!("[download] Completed: {} ({} bytes)", model_id, total_bytes);
+
+    Ok(())
+}
+
+/// Byte counters shared by the per-file download tasks of one model.
+struct DownloadTotals {
+    model_id: String,
+    /// Catalog sizes, plus the `Content-Length` of files the catalog lists as 0
+    total_bytes: AtomicU64,
+    /// Files whose size has been added to `total_bytes` from `Content-Length`
+    sized_files: Mutex<HashSet<String>>,
+    downloaded: AtomicU64,
+    /// Bytes fetched in this session only, for the speed estimate
+    session: AtomicU64,
+    start_time: Instant,
+}
+
+/// Download every file of a catalog entry into `model_dir`, all files at
+/// once. Each file is streamed into a `.part` file, resumed with a `Range`
+/// request when one already exists, and renamed to its final name once
+/// complete. A failing file stops its siblings; their `.part` files are kept.
+async fn download_model_files(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    entry: &ModelCatalogEntry,
+    model_dir: &Path,
+    control: &Arc<DownloadControl>,
+) -> Result<(), String> {
+    let totals = Arc::new(DownloadTotals {
+        model_id: entry.id.clone(),
+        total_bytes: AtomicU64::new(entry.files.total_bytes()),
+        sized_files: Mutex::new(HashSet::new()),
+        downloaded: AtomicU64::new(0),
+        session: AtomicU64::new(0),
+        start_time: Instant::now(),
+    });
+    let abort = Arc::new(AtomicBool::new(false));
+    control
+        .total_bytes
+        .store(totals.total_bytes.load(Ordering::SeqCst), Ordering::SeqCst);
+
+    // Download files (model, any additional shards and mmproj) concurrently
+    let mut tasks = Vec::new();
+    for file in entry.files.all() {
+        let file_path = model_dir.join(&file.filename);
+        if file_path.exists() {
+            // Completed in an earlier attempt
+            let size = std::fs::metadata(&file_path).map(|m| m.len()).unwrap_or(file.size_bytes);
+            if file.size_bytes == 0 {
+                totals.total_bytes.fetch_add(size, Ordering::SeqCst);
+            }
+            let done = totals.downloaded.fetch_add(size, Ordering::SeqCst);
+            control.downloaded_bytes.store(done + size, Ordering::SeqCst);
+            continue;
         }
-        files
-    };
 
-    let client = reqwest::Client::new();
-    let mut total_downloaded: u64 = 0;
-    let start_time = Instant::now();
-
-    for file in files_to_download {
-        if cancel_token.load(Ordering::SeqCst) {
-            // Cleanup on cancel
-            let _ = std::fs::remove_dir_all(&model_dir);
-            {
-                let mut downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
-                downloads.remove(&model_id);
+        let (app, client, file, model_dir) =
+            (app.clone(), client.clone(), file.clone(), model_dir.to_path_buf());
+        let (control, abort, totals) = (control.clone(), abort.clone(), totals.clone());
+        control.files_left.fetch_add(1, Ordering::SeqCst);
+        tasks.push(tokio::spawn(async move {
+            let result =
+                download_file_with_retry(&app, &client, &file, &model_dir, &control, &abort, &totals)
+                    .await;
+            control.files_left.fetch_sub(1, Ordering::SeqCst);
+            if result.is_err() {
+                abort.store(true, Ordering::SeqCst);
             }
-            {
-                let mut progress = state.downloading_progress.lock().map_err(|e| e.to_string())?;
-                progress.remove(&model_id);
+            result
+        }));
+    }
+
+    let mut first_error = None;
+    for task in tasks {
+        let result = task.await.map_err(|e| e.to_string()).and_then(|r| r);
+        if let Err(e) = result {
+            abort.store(true, Ordering::SeqCst);
+            // Siblings only report that they were stopped; keep the real cause
+            if first_error.as_deref().map_or(true, |f| f == STOPPED) {
+                first_error = Some(e);
             }
-            return Err("Download cancelled".to_string());
         }
+    }
 
-        let file_path = model_dir.join(&file.filename);
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
 
-        // Make request
-        let response = client
-            .get(&file.url)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
+/// How a single file download failed. Transient failures (network errors,
+/// 5xx responses, truncated streams) are retried by `download_file_with_retry`.
+enum FileError {
+    Stopped,
+    Transient(String),
+    Fatal(String),
+}
 
-        if !response.status().is_success() {
-            let _ = std::fs::remove_dir_all(&model_dir);
-            {
-                let mut downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
-                downloads.remove(&model_id);
+impl FileError {
+    fn fatal(e: impl ToString) -> Self {
+        FileError::Fatal(e.to_string())
+    }
+
+    fn into_message(self) -> String {
+        match self {
+            FileError::Stopped => STOPPED.to_string(),
+            FileError::Transient(e) | FileError::Fatal(e) => e,
+        }
+    }
+}
+
+const STOPPED: &str = "Download stopped";
+const MAX_DOWNLOAD_ATTEMPTS: u32 = 5;
+
+/// Retry transient failures with exponential backoff (1s, 2s, 4s, ...).
+/// Each retry resumes from the `.part` file via `Range`.
+async fn download_file_with_retry(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    file: &ModelFile,
+    model_dir: &Path,
+    control: &DownloadControl,
+    abort: &AtomicBool,
+    totals: &DownloadTotals,
+) -> Result<(), String> {
+    let mut attempt = 1;
+    loop {
+        let mut counted = 0;
+        let result =
+            download_file(app, client, file, model_dir, control, abort, totals, &mut counted).await;
+        let error = match result {
+            Ok(()) => return Ok(()),
+            Err(FileError::Transient(e)) if attempt < MAX_DOWNLOAD_ATTEMPTS => e,
+            Err(e) => return Err(e.into_message()),
+        };
+
+        // The next attempt re-counts the bytes already on disk
+        totals.downloaded.fetch_sub(counted, Ordering::SeqCst);
+
+        println!(
+            "[download] {} failed (attempt {}/{}): {}",
+            file.filename, attempt, MAX_DOWNLOAD_ATTEMPTS, error
+        );
+        let _ = app.emit(
+            DOWNLOAD_RETRY,
+            DownloadRetryPayload {
+                model_id: totals.model_id.clone(),
+                filename: file.filename.clone(),
+                attempt: attempt + 1,
+                max_attempts: MAX_DOWNLOAD_ATTEMPTS,
+                error,
+            },
+        );
+
+        // Sleep in short steps so cancel/pause still takes effect quickly
+        let backoff = Duration::from_secs(1 << (attempt - 1));
+        let wake = Instant::now() + backoff;
+        while Instant::now() < wake {
+            if control.should_stop() || abort.load(Ordering::SeqCst) {
+                return Err(STOPPED.to_string());
             }
-            {
-                let mut progress = state.downloading_progress.lock().map_err(|e| e.to_string())?;
-                progress.remove(&model_id);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        attempt += 1;
+    }
+}
+
+fn hugging_face_token(state: &LlamaServerManager) -> Option<String> {
+    let settings = state.app_settings.lock().ok()?;
+    settings
+        .models
+        .hf_token
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+fn is_hugging_face_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .map_or(false, |host| host == "huggingface.co" || host.ends_with(".huggingface.co"))
+}
+
+/// Authenticate requests to huggingface.co so gated models can be fetched.
+/// reqwest drops the header when the download redirects to another host.
+fn with_hf_token(
+    request: reqwest::RequestBuilder,
+    url: &str,
+    token: Option<&str>,
+) -> reqwest::RequestBuilder {
+    match token {
+        Some(token) if is_hugging_face_url(url) => request.bearer_auth(token),
+        _ => request,
+    }
+}
+
+/// A 401/403 from Hugging Face means the model is gated, not that it's gone.
+fn download_status_error(status: reqwest::StatusCode, url: &str, has_token: bool) -> String {
+    let denied = status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN;
+    if !denied || !is_hugging_face_url(url) {
+        return describe_http_status(status);
+    }
+    if has_token {
+        format!(
+            "Hugging Face denied access ({}): accept the model's license on huggingface.co with the account the token belongs to",
+            status
+        )
+    } else {
+        format!(
+            "This model requires a Hugging Face token ({}): accept its license on huggingface.co and add a token in Settings",
+            status
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn download_file(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    file: &ModelFile,
+    model_dir: &Path,
+    control: &DownloadControl,
+    abort: &AtomicBool,
+    totals: &DownloadTotals,
+    counted: &mut u64,
+) -> Result<(), FileError> {
+    let should_stop = || control.should_stop() || abort.load(Ordering::SeqCst);
+    if should_stop() {
+        return Err(FileError::Stopped);
+    }
+
+    let file_path = model_dir.join(&file.filename);
+    let part_path = partial_download_path(model_dir, &file.filename);
+    let offset = std::fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+
+    let hf_token = hugging_face_token(&app.state::<LlamaServerManager>());
+    let mut request = with_hf_token(client.get(&file.url), &file.url, hf_token.as_deref());
+    if offset > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+    let response = request.send().await.map_err(|e| {
+        let state = app.state::<LlamaServerManager>();
+        FileError::Transient(describe_request_error(&state, &e))
+    })?;
+
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && offset > 0 {
+        // The .part file should already hold the whole file, but an
+        // oversized or corrupt one must not be accepted as finished
+        let _verifying = Verifying::begin(control);
+        let size_ok = file.size_bytes == 0 || offset == file.size_bytes;
+        let hash_ok = match file.sha256 {
+            Some(ref expected) => {
+                let hasher = hash_partial(&part_path).await.map_err(FileError::Fatal)?;
+                let actual = format!("{:x}", hasher.finalize());
+                actual.eq_ignore_ascii_case(expected.trim())
             }
-            return Err(format!("HTTP error: {}", response.status()));
+            None => true,
+        };
+        if !size_ok || !hash_ok {
+            let _ = std::fs::remove_file(&part_path);
+            return Err(FileError::Fatal(format!("Checksum mismatch for {}", file.filename)));
         }
+        std::fs::rename(&part_path, &file_path).map_err(FileError::fatal)?;
+        totals.downloaded.fetch_add(offset, Ordering::SeqCst);
+        return Ok(());
+    }
+    if !response.status().is_success() {
+        let message = download_status_error(response.status(), &file.url, hf_token.is_some());
+        return Err(if response.status().is_server_error() {
+            FileError::Transient(message)
+        } else {
+            FileError::Fatal(message)
+        });
+    }
+
+    // Servers that ignore Range answer 200 with the whole file
+    let resumed = offset > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut file_downloaded = if resumed { offset } else { 0 };
+    let expected_size = response
+        .content_length()
+        .map(|len| len + file_downloaded)
+        .unwrap_or(file.size_bytes);
+    // The catalog doesn't know this file's size (e.g. imported models), so
+    // count it towards the total the first time a response reveals it
+    let newly_sized = file.size_bytes == 0
+        && expected_size > 0
+        && totals
+            .sized_files
+            .lock()
+            .map(|mut sized| sized.insert(file.filename.clone()))
+            .unwrap_or(false);
+    if newly_sized {
+        let total = totals.total_bytes.fetch_add(expected_size, Ordering::SeqCst) + expected_size;
+        control.total_bytes.store(total, Ordering::SeqCst);
+    }
 
-        // Create file
-        let mut out_file = tokio::fs::File::create(&file_path)
+    let mut out_file = if resumed {
+        println!("[download] Resuming {} at {} bytes", file.filename, offset);
+        let _ = app.emit(
+            DOWNLOAD_RESUMED,
+            DownloadResumedPayload {
+                model_id: totals.model_id.clone(),
+                filename: file.filename.clone(),
+                offset_bytes: offset,
+            },
+        );
+        tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(&part_path)
             .await
-            .map_err(|e| e.to_string())?;
-
-        // Stream download
-        let mut stream = response.bytes_stream();
-        let mut file_downloaded: u64 = 0;
-
-        while let Some(chunk_result) = stream.next().await {
-            if cancel_token.load(Ordering::SeqCst) {
-                drop(out_file);
-                let _ = std::fs::remove_dir_all(&model_dir);
-                {
-                    let mut downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
-                    downloads.remove(&model_id);
-                }
-                {
-                    let mut progress = state.downloading_progress.lock().map_err(|e| e.to_string())?;
-                    progress.remove(&model_id);
-                }
-                return Err("Download cancelled".to_string());
-            }
+            .map_err(FileError::fatal)?
+    } else {
+        tokio::fs::File::create(&part_path)
+            .await
+            .map_err(FileError::fatal)?
+    };
+    totals.downloaded.fetch_add(file_downloaded, Ordering::SeqCst);
+    *counted += file_downloaded;
 
-            let chunk = chunk_result.map_err(|e| e.to_string())?;
-            out_file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+    // Hash as we go; a resumed file first needs the bytes already on disk
+    let mut hasher = match file.sha256 {
+        Some(_) if resumed => Some(hash_partial(&part_path).await.map_err(FileError::Fatal)?),
+        Some(_) => Some(Sha256::new()),
+        None => None,
+    };
 
-            file_downloaded += chunk.len() as u64;
-            total_downloaded += chunk.len() as u64;
+    // Stream download
+    let mut stream = response.bytes_stream();
 
-            let percent = (total_downloaded as f32 / total_bytes as f32) * 100.0;
-            let elapsed = start_time.elapsed().as_secs_f64();
-            let speed_bps = if elapsed > 0.0 {
-                (total_downloaded as f64 / elapsed) as u64
-            } else {
-                0
-            };
+    while let Some(chunk_result) = stream.next().await {
+        if should_stop() {
+            let _ = out_file.flush().await;
+            return Err(FileError::Stopped);
+        }
 
-            // Update progress
-            {
-                let mut progress_map = state.downloading_progress.lock().map_err(|e| e.to_string())?;
-                progress_map.insert(model_id.clone(), percent);
-            }
+        let chunk = chunk_result.map_err(|e| FileError::Transient(e.to_string()))?;
+        out_file.write_all(&chunk).await.map_err(FileError::fatal)?;
+        if let Some(ref mut hasher) = hasher {
+            hasher.update(&chunk);
+        }
 
-            // Emit progress event (throttled to every 100ms worth of data)
-            if file_downloaded % (1024 * 100) < chunk.len() as u64 {
-                let _ = app.emit(
-                    "download:progress",
-                    DownloadProgressPayload {
-                        model_id: model_id.clone(),
-                        downloaded_bytes: total_downloaded,
-                        total_bytes,
-                        percent,
-                        speed_bps,
-                    },
-                );
-            }
+        let len = chunk.len() as u64;
+        file_downloaded += len;
+        let total_downloaded = totals.downloaded.fetch_add(len, Ordering::SeqCst) + len;
+        *counted += len;
+        let session_downloaded = totals.session.fetch_add(len, Ordering::SeqCst) + len;
+
+        let total_bytes = totals.total_bytes.load(Ordering::SeqCst);
+        let percent = (total_bytes > 0)
+            .then(|| (total_downloaded as f32 / total_bytes as f32 * 100.0).min(100.0));
+        let elapsed = totals.start_time.elapsed().as_secs_f64();
+        // Combined throughput of every file in flight
+        let speed_bps = if elapsed > 0.0 {
+            (session_downloaded as f64 / elapsed) as u64
+        } else {
+            0
+        };
+
+        // Update progress
+        control.downloaded_bytes.store(total_downloaded, Ordering::SeqCst);
+        control.speed_bps.store(speed_bps, Ordering::SeqCst);
+        {
+            let state = app.state::<LlamaServerManager>();
+            let mut progress_map = state.downloading_progress.lock().map_err(FileError::fatal)?;
+            progress_map.insert(totals.model_id.clone(), percent);
         }
 
-        out_file.flush().await.map_err(|e| e.to_string())?;
+        // Emit progress event (throttled to every 100ms worth of data)
+        if file_downloaded % (1024 * 100) < len {
+            let _ = app.emit(
+                DOWNLOAD_PROGRESS,
+                DownloadProgressPayload {
+                    model_id: totals.model_id.clone(),
+                    downloaded_bytes: total_downloaded,
+                    total_bytes,
+                    percent,
+                    speed_bps,
+                },
+            );
+        }
     }
 
-    // Cleanup tracking
-    {
-        let mut downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
-        downloads.remove(&model_id);
+    out_file.flush().await.map_err(FileError::fatal)?;
+    drop(out_file);
+    let _verifying = Verifying::begin(control);
+
+    // A stream that ends early is a dropped connection (unknowable without
+    // either a catalog size or Content-Length)
+    if expected_size > 0 && file_downloaded != expected_size {
+        return Err(FileError::Transient(format!(
+            "Download of {} stopped at {} of {} bytes; it will resume on retry",
+            file.filename, file_downloaded, expected_size
+        )));
     }
-    {
-        let mut progress = state.downloading_progress.lock().map_err(|e| e.to_string())?;
-        progress.remove(&model_id);
+
+    if let (Some(expected), Some(hasher)) = (&file.sha256, hasher) {
+        let actual = format!("{:x}", hasher.finalize());
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            let _ = std::fs::remove_file(&part_path);
+            return Err(FileError::Fatal(format!("Checksum mismatch for {}", file.filename)));
+        }
     }
+    std::fs::rename(&part_path, &file_path).map_err(FileError::fatal)
+}
 
-    // Emit completion
-    let _ = app.emit("download:complete", model_id.clone());
-    println!("[download] Completed: {}", model_id);
+/// Counts a file as being checked for `list_downloads` until dropped.
+struct Verifying<'a>(&'a AtomicU32);
 
-    Ok(())
+impl<'a> Verifying<'a> {
+    fn begin(control: &'a DownloadControl) -> Self {
+        control.verifying.fetch_add(1, Ordering::SeqCst);
+        Self(&control.verifying)
+    }
+}
+
+impl Drop for Verifying<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Start a SHA-256 over the bytes already in a partially downloaded file.
+fn hash_existing(path: &Path) -> Result<Sha256, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+    Ok(hasher)
+}
+
+/// `hash_existing` from async code; a `.part` file can be several GB, so
+/// reading it must not block the runtime.
+async fn hash_partial(path: &Path) -> Result<Sha256, String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || hash_existing(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// How long the speed probe in `estimate_download` samples for.
+const PROBE_DURATION: Duration = Duration::from_secs(1);
+/// Upper bound on what the probe requests, so fast links finish quickly.
+const PROBE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Measure throughput from the model's host for about a second and
+/// estimate how long the full download would take. Nothing is written to
+/// disk. Hosts that ignore the `Range` header are sampled the same way and
+/// the stream is simply dropped after the probe window.
+#[tauri::command]
+pub async fn estimate_download(
+    args: EstimateDownloadArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<DownloadEstimate, String> {
+    let model_id = args.model_id;
+
+    let catalog = load_or_create_catalog(&app)?;
+    let entry = catalog
+        .models
+        .iter()
+        .find(|e| e.id == model_id)
+        .ok_or_else(|| format!("Model {} not found in catalog", model_id))?;
+
+    let client = http_client(&state)?;
+    let url = &entry.files.model.url;
+    let hf_token = hugging_face_token(&state);
+    let response = with_hf_token(client.get(url), url, hf_token.as_deref())
+        .header(reqwest::header::RANGE, format!("bytes=0-{}", PROBE_BYTES - 1))
+        .send()
+        .await
+        .map_err(|e| describe_request_error(&state, &e))?;
+
+    if !response.status().is_success() {
+        return Err(download_status_error(response.status(), url, hf_token.is_some()));
+    }
+    let range_supported = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+
+    // Time from the first byte so connection setup doesn't skew the rate
+    let mut stream = response.bytes_stream();
+    let mut received: u64 = 0;
+    let mut first_byte: Option<Instant> = None;
+
+    while let Ok(Some(chunk)) = tokio::time::timeout(PROBE_DURATION, stream.next()).await {
+        let chunk = chunk.map_err(|e| e.to_string())?;
+        let started = *first_byte.get_or_insert_with(Instant::now);
+        received += chunk.len() as u64;
+        if started.elapsed() >= PROBE_DURATION || received >= PROBE_BYTES {
+            break;
+        }
+    }
+    drop(stream);
+
+    let elapsed = first_byte.map(|t| t.elapsed().as_secs_f64()).unwrap_or(0.0);
+    let speed_bps = if elapsed > 0.0 {
+        (received as f64 / elapsed) as u64
+    } else {
+        0
+    };
+
+    let total_bytes = entry.files.total_bytes();
+    let estimated_secs = if speed_bps > 0 {
+        Some(total_bytes / speed_bps)
+    } else {
+        None
+    };
+
+    println!(
+        "[download] Estimate for {}: {} B/s, ~{:?}s (range: {})",
+        model_id, speed_bps, estimated_secs, range_supported
+    );
+
+    Ok(DownloadEstimate {
+        model_id,
+        total_bytes,
+        speed_bps,
+        estimated_secs,
+        range_supported,
+    })
 }
 
 #[tauri::command]
@@ -455,14 +1137,137 @@ pub fn cancel_download(
     let model_id = args.model_id;
 
     let downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
-    if let Some(cancel_token) = downloads.get(&model_id) {
-        cancel_token.store(true, Ordering::SeqCst);
+    if let Some(control) = downloads.get(&model_id) {
+        control.cancelled.store(true, Ordering::SeqCst);
         println!("[download] Cancelled: {}", model_id);
+    } else {
+        // A paused download has no task; just discard what it left behind
+        drop(downloads);
+        discard_partial_download(&state, &model_id)?;
     }
 
     Ok(())
 }
 
+/// Stop a download but keep the `.part` files so it can be resumed later.
+#[tauri::command]
+pub fn pause_download(
+    args: CancelDownloadArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    let downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
+    let control = downloads
+        .get(&args.model_id)
+        .ok_or_else(|| format!("{} is not downloading", args.model_id))?;
+    control.paused.store(true, Ordering::SeqCst);
+    println!("[download] Pausing: {}", args.model_id);
+    Ok(())
+}
+
+/// Every active, paused or interrupted download with byte counts, speed and ETA.
+#[tauri::command]
+pub fn list_downloads(
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Vec<DownloadInfo>, String> {
+    collect_downloads(&app, &state)
+}
+
+/// Active downloads plus models with leftover .part files, paused or not.
+fn collect_downloads(app: &AppHandle, state: &LlamaServerManager) -> Result<Vec<DownloadInfo>, String> {
+    let catalog = load_or_create_catalog(app)?;
+    let downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
+    let mut out = Vec::new();
+
+    for entry in &catalog.models {
+        if let Some(control) = downloads.get(&entry.id) {
+            let downloaded_bytes = control.downloaded_bytes.load(Ordering::SeqCst);
+            let total_bytes = match control.total_bytes.load(Ordering::SeqCst) {
+                0 => entry.files.total_bytes(),
+                n => n,
+            };
+            let speed_bps = control.speed_bps.load(Ordering::SeqCst);
+            let eta_secs = (speed_bps > 0)
+                .then(|| total_bytes.saturating_sub(downloaded_bytes) / speed_bps);
+
+            out.push(DownloadInfo {
+                model_id: entry.id.clone(),
+                name: entry.name.clone(),
+                state: if control.paused.load(Ordering::SeqCst) {
+                    "pausing".to_string()
+                } else if control.is_verifying() {
+                    "verifying".to_string()
+                } else {
+                    "downloading".to_string()
+                },
+                downloaded_bytes,
+                total_bytes,
+                speed_bps,
+                eta_secs,
+            });
+        } else if has_partial_download(&state.models_dir, entry) {
+            let model_dir = get_model_dir(&state.models_dir, &entry.id);
+            let size = |path: PathBuf| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            let downloaded_bytes = entry
+                .files
+                .all()
+                .iter()
+                .map(|f| {
+                    size(model_dir.join(&f.filename)) + size(partial_download_path(&model_dir, &f.filename))
+                })
+                .sum();
+
+            out.push(DownloadInfo {
+                model_id: entry.id.clone(),
+                name: entry.name.clone(),
+                state: partial_download_state(&state.models_dir, entry),
+                downloaded_bytes,
+                total_bytes: entry.files.total_bytes(),
+                speed_bps: 0,
+                eta_secs: None,
+            });
+        }
+    }
+
+    Ok(out)
+}
+
+/// "paused" if the user paused the download, "interrupted" if its `.part`
+/// files were left by a crash, quit or failure. Both can be resumed.
+fn partial_download_state(models_dir: &Path, entry: &ModelCatalogEntry) -> String {
+    if is_download_paused(models_dir, entry) {
+        "paused".to_string()
+    } else {
+        "interrupted".to_string()
+    }
+}
+
+/// Continue a paused (or interrupted) download from its `.part` files.
+#[tauri::command]
+pub async fn resume_download(
+    args: DownloadModelArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    download_model(args, app, state).await
+}
+
+fn discard_partial_download(state: &LlamaServerManager, model_id: &str) -> Result<(), String> {
+    let model_dir = get_model_dir(&state.models_dir, model_id);
+    if !model_dir.exists() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(&model_dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().map_or(false, |ext| ext == "part") {
+            std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+    }
+    let _ = std::fs::remove_file(pause_marker_path(&model_dir));
+    println!("[download] Discarded partial download: {}", model_id);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn delete_model(
     args: DeleteModelArgs,
@@ -492,3 +1297,411 @@ pub fn delete_model(
 
     Ok(())
 }
+
+/// Check every downloaded catalog model for missing or damaged files.
+/// Nothing is deleted; the caller decides whether to re-download.
+#[tauri::command]
+pub async fn verify_all_models(
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Vec<ModelVerifyResult>, String> {
+    let catalog = load_or_create_catalog(&app)?;
+    let downloading: Vec<String> = {
+        let downloads = state.active_downloads.lock().map_err(|e| e.to_string())?;
+        downloads.keys().cloned().collect()
+    };
+
+    let entries: Vec<_> = catalog
+        .models
+        .iter()
+        .filter(|entry| !downloading.contains(&entry.id))
+        .filter(|entry| is_model_downloaded(&state.models_dir, entry))
+        .collect();
+
+    let total = entries.len();
+    let mut results = Vec::with_capacity(total);
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let problems = verify_model_files(&state.models_dir, entry);
+        let status = if problems.is_empty() { "ok" } else { "corrupt" };
+
+        println!("[verify] {}: {}", entry.id, status);
+        let _ = app.emit(
+            VERIFY_PROGRESS,
+            VerifyProgressPayload {
+                model_id: entry.id.clone(),
+                index: index + 1,
+                total,
+                status: status.to_string(),
+            },
+        );
+
+        results.push(ModelVerifyResult {
+            model_id: entry.id.clone(),
+            status: status.to_string(),
+            problems,
+        });
+    }
+
+    Ok(results)
+}
+
+/// Collect the catalog, per-model download state, the files actually on disk
+/// and any in-progress downloads into one JSON-serializable snapshot that
+/// can be attached to a "model won't load" report.
+#[tauri::command]
+pub async fn export_model_state(
+    args: Option<ExportModelStateArgs>,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<ModelStateExport, String> {
+    let include_hashes = args.unwrap_or_default().include_hashes;
+
+    let catalog = load_or_create_catalog(&app)?;
+    let models = collect_models(&app, &state)?;
+    let downloads = collect_downloads(&app, &state)?;
+    let current_model_id = state.current_model_id.lock().map_err(|e| e.to_string())?.clone();
+
+    let models_dir = state.models_dir.clone();
+    let entries = catalog.models.clone();
+    // Stat'ing (and possibly hashing) every file must not block the runtime
+    let local = tokio::task::spawn_blocking(move || {
+        entries
+            .iter()
+            .filter(|entry| get_model_dir(&models_dir, &entry.id).exists())
+            .map(|entry| local_model_state(&models_dir, entry, include_hashes))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(ModelStateExport {
+        exported_at: unix_ms(),
+        models_dir: state.models_dir.display().to_string(),
+        current_model_id,
+        catalog,
+        models,
+        local,
+        downloads,
+    })
+}
+
+fn local_model_state(models_dir: &Path, entry: &ModelCatalogEntry, include_hashes: bool) -> LocalModelState {
+    let model_dir = get_model_dir(models_dir, &entry.id);
+    let size = |path: &Path| std::fs::metadata(path).ok().map(|m| m.len());
+
+    let files = entry
+        .files
+        .all()
+        .into_iter()
+        .map(|file| {
+            let path = model_dir.join(&file.filename);
+            let size_bytes = size(&path);
+            let sha256 = if include_hashes && size_bytes.is_some() {
+                hash_existing(&path)
+                    .map(|hasher| format!("{:x}", hasher.finalize()))
+                    .ok()
+            } else {
+                None
+            };
+
+            LocalFileState {
+                filename: file.filename.clone(),
+                expected_bytes: file.size_bytes,
+                expected_sha256: file.sha256.clone(),
+                size_bytes,
+                partial_bytes: size(&partial_download_path(&model_dir, &file.filename)),
+                sha256,
+            }
+        })
+        .collect();
+
+    LocalModelState {
+        model_id: entry.id.clone(),
+        model_dir: model_dir.display().to_string(),
+        files,
+        problems: verify_model_files(models_dir, entry),
+    }
+}
+
+/// Report duplicate ids and ids whose downloaded files don't match a
+/// proposed catalog, before it replaces the current one.
+#[tauri::command]
+pub fn check_catalog_conflicts(
+    args: CheckCatalogConflictsArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Vec<CatalogConflict>, String> {
+    let current = load_or_create_catalog(&app)?;
+    Ok(find_catalog_conflicts(&state.models_dir, &current, &args.catalog))
+}
+
+/// Replace the catalog with the one at the `catalogUrl` setting, keeping
+/// locally imported models. On any failure the current catalog is left
+/// untouched. Returns the number of models in the new catalog.
+#[tauri::command]
+pub async fn refresh_catalog(
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<usize, String> {
+    let url = {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        settings
+            .models
+            .catalog_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(str::to_string)
+    };
+    let url = url.ok_or_else(|| "No catalog URL is set".to_string())?;
+
+    let response = http_client(&state)?
+        .get(&url)
+        .timeout(CATALOG_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| describe_request_error(&state, &e))?;
+    if !response.status().is_success() {
+        return Err(describe_http_status(response.status()));
+    }
+    let content = response.text().await.map_err(|e| e.to_string())?;
+    let incoming =
+        parse_catalog(&content).map_err(|e| format!("{} is not a valid model catalog: {}", url, e))?;
+
+    let current = load_or_create_catalog(&app)?;
+    let merged = merge_remote_catalog(&current, incoming);
+    let conflicts = find_catalog_conflicts(&state.models_dir, &current, &merged);
+    if !conflicts.is_empty() {
+        let details: Vec<String> = conflicts.into_iter().map(|c| c.detail).collect();
+        return Err(format!(
+            "Catalog from {} conflicts with installed models: {}",
+            url,
+            details.join("; ")
+        ));
+    }
+
+    save_catalog(&app, &merged)?;
+    println!("[catalog] Refreshed from {} ({} models)", url, merged.models.len());
+    let _ = app.emit(MODELS_CHANGED, ());
+    Ok(merged.models.len())
+}
+
+/// Fetching the catalog is a small request; don't let it hang forever.
+const CATALOG_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Add a GGUF that already exists on disk to the catalog. Files are
+/// hardlinked into the model directory when possible and copied otherwise;
+/// secondary shards of a split model next to `source_path` come along.
+#[tauri::command]
+pub async fn import_model(
+    args: ImportModelArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<String, String> {
+    let model_path = PathBuf::from(&args.source_path);
+    if !model_path.is_file() {
+        return Err(format!("{} is not a file", model_path.display()));
+    }
+    let mmproj_path = args.mmproj_path.as_ref().map(PathBuf::from);
+    if let Some(ref mmproj) = mmproj_path {
+        if !mmproj.is_file() {
+            return Err(format!("{} is not a file", mmproj.display()));
+        }
+    }
+
+    let name = args.name.trim().to_string();
+    let model_id = slugify(&name);
+    if model_id.is_empty() {
+        return Err("Model name must contain letters or digits".to_string());
+    }
+
+    let mut catalog = load_or_create_catalog(&app)?;
+    let model_dir = get_model_dir(&state.models_dir, &model_id);
+    if catalog.models.iter().any(|e| e.id == model_id) || model_dir.exists() {
+        return Err(format!("A model with id {} already exists", model_id));
+    }
+
+    let mut shards = find_secondary_shards(&model_path);
+    shards.sort();
+    let mut sources = vec![model_path.clone()];
+    sources.extend(shards.iter().cloned());
+    sources.extend(mmproj_path.clone());
+
+    // Copying several GB must not block the async runtime
+    let dest_dir = model_dir.clone();
+    tokio::task::spawn_blocking(move || import_files(&sources, &dest_dir))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let describe = |path: &PathBuf| ModelFile {
+        filename: path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        url: String::new(),
+        size_bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        sha256: None,
+    };
+
+    catalog.models.push(ModelCatalogEntry {
+        id: model_id.clone(),
+        name,
+        description: format!("Imported from {}", model_path.display()),
+        size_label: String::new(),
+        license: None,
+        source_url: None,
+        author: None,
+        capabilities: ModelCapabilities {
+            vision: mmproj_path.is_some(),
+            thinking: false,
+            tools: false,
+        },
+        files: ModelFiles {
+            model: describe(&model_path),
+            shards: shards.iter().map(describe).collect(),
+            mmproj: mmproj_path.as_ref().map(describe),
+        },
+    });
+    if let Err(e) = save_catalog(&app, &catalog) {
+        let _ = std::fs::remove_dir_all(&model_dir);
+        return Err(e);
+    }
+
+    println!("[model] Imported {} as {}", model_path.display(), model_id);
+    let _ = app.emit(MODELS_CHANGED, ());
+    Ok(model_id)
+}
+
+/// Hardlink (or copy, across filesystems) each file into `dest_dir`,
+/// removing the directory again if anything fails.
+fn import_files(sources: &[PathBuf], dest_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dest_dir).map_err(|e| e.to_string())?;
+
+    for src in sources {
+        let dest = dest_dir.join(src.file_name().unwrap_or_default());
+        let linked = std::fs::hard_link(src, &dest).is_ok();
+        if !linked {
+            if let Err(e) = std::fs::copy(src, &dest) {
+                let _ = std::fs::remove_dir_all(dest_dir);
+                return Err(format!("Failed to copy {}: {}", src.display(), e));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Move the flat "legacy" model (a `*.gguf` directly in the models directory)
+/// into its own `models_dir/<id>/` folder and add it to the catalog, so it
+/// can be verified, deleted and selected like any downloaded model.
+/// Returns the new model id.
+#[tauri::command]
+pub fn migrate_legacy_model(
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<String, String> {
+    let (model_path, mmproj_path) = scan_models_dir(&state.models_dir)
+        .filter(|(mp, _)| mp.parent() == Some(state.models_dir.as_path()))
+        .ok_or_else(|| "No legacy model found".to_string())?;
+
+    let display_name = model_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Legacy Model".to_string());
+
+    let mut catalog = load_or_create_catalog(&app)?;
+    let model_id = unique_model_id(&state.models_dir, &catalog, &slugify(&display_name));
+    let model_dir = get_model_dir(&state.models_dir, &model_id);
+    std::fs::create_dir_all(&model_dir).map_err(|e| e.to_string())?;
+
+    // Move files, undoing earlier moves if a later step fails
+    let mut to_move = vec![model_path.clone()];
+    to_move.extend(find_secondary_shards(&model_path));
+    to_move.extend(mmproj_path.clone());
+
+    let undo_moves = |moved: &[(PathBuf, PathBuf)]| {
+        for (from, to) in moved.iter().rev() {
+            let _ = std::fs::rename(to, from);
+        }
+        let _ = std::fs::remove_dir(&model_dir);
+    };
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for src in &to_move {
+        let dest = model_dir.join(src.file_name().unwrap_or_default());
+        if let Err(e) = std::fs::rename(src, &dest) {
+            undo_moves(&moved);
+            return Err(format!("Failed to move {}: {}", src.display(), e));
+        }
+        moved.push((src.clone(), dest));
+    }
+
+    let describe = |path: &PathBuf| ModelFile {
+        filename: path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        url: String::new(),
+        size_bytes: std::fs::metadata(model_dir.join(path.file_name().unwrap_or_default()))
+            .map(|m| m.len())
+            .unwrap_or(0),
+        sha256: None,
+    };
+
+    let entry = ModelCatalogEntry {
+        id: model_id.clone(),
+        name: display_name,
+        description: "Existing model from previous installation".to_string(),
+        size_label: String::new(),
+        license: None,
+        source_url: None,
+        author: None,
+        capabilities: ModelCapabilities {
+            vision: mmproj_path.is_some(),
+            thinking: false,
+            tools: false,
+        },
+        files: ModelFiles {
+            model: describe(&model_path),
+            shards: to_move[1..]
+                .iter()
+                .filter(|p| Some(*p) != mmproj_path.as_ref())
+                .map(describe)
+                .collect(),
+            mmproj: mmproj_path.as_ref().map(describe),
+        },
+    };
+
+    catalog.models.push(entry);
+    if let Err(e) = save_catalog(&app, &catalog) {
+        // Without a catalog entry the moved files wouldn't be found at all
+        undo_moves(&moved);
+        return Err(e);
+    }
+
+    // A running server keeps its open file handle, so only the paths used
+    // for the next (re)start need to point at the new location
+    {
+        let mut current = state.current_model_id.lock().map_err(|e| e.to_string())?;
+        if current.as_deref() == Some("legacy") {
+            *current = Some(model_id.clone());
+            *state.model_path.lock().map_err(|e| e.to_string())? =
+                model_dir.join(model_path.file_name().unwrap_or_default());
+            *state.mmproj_path.lock().map_err(|e| e.to_string())? = mmproj_path
+                .as_ref()
+                .map(|p| model_dir.join(p.file_name().unwrap_or_default()));
+        }
+    }
+    {
+        let mut settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        if settings.defaults.model_id.as_deref() == Some("legacy") {
+            settings.defaults.model_id = Some(model_id.clone());
+            save_settings(&settings)?;
+            // Otherwise the settings UI would save "legacy" back
+            let _ = app.emit(SETTINGS_CHANGED, &*settings);
+        }
+    }
+
+    println!("[model] Migrated legacy model to {}", model_id);
+    let _ = app.emit(MODELS_CHANGED, ());
+    Ok(model_id)
+}
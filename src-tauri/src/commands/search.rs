@@ -0,0 +1,69 @@
+// src-tauri/src/commands/search.rs
+
+use rusqlite::params;
+use tauri::State;
+
+use crate::db::open_db;
+use crate::search::{highlight_snippet, parse_search_query, MATCH_END, MATCH_START};
+use crate::state::LlamaServerManager;
+use crate::types::{MessageSearchResult, SearchMessagesArgs};
+
+const DEFAULT_SEARCH_LIMIT: u32 = 50;
+/// Number of tokens FTS5 includes in each snippet
+const SNIPPET_TOKENS: u32 = 16;
+
+/// Search message content across all chats, best matches first. Snippets are
+/// HTML-escaped with matched terms wrapped in `<mark>`.
+#[tauri::command]
+pub fn search_messages(
+    args: SearchMessagesArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Vec<MessageSearchResult>, String> {
+    let query = parse_search_query(&args.query)?;
+    let limit = args.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, 500);
+
+    let conn = open_db(&state.db_path)?;
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT m.id, m.conversation_id, c.title, m.role,
+                   snippet(messages_fts, 1, ?2, ?3, '…', ?4), m.created_at
+            FROM messages_fts
+            JOIN messages m ON m.id = messages_fts.message_id
+            JOIN conversations c ON c.id = m.conversation_id
+            WHERE messages_fts MATCH ?1
+            ORDER BY bm25(messages_fts)
+            LIMIT ?5
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(
+            params![
+                query.to_fts_match(),
+                MATCH_START,
+                MATCH_END,
+                SNIPPET_TOKENS,
+                limit
+            ],
+            |row| {
+                let snippet: String = row.get(4)?;
+                Ok(MessageSearchResult {
+                    message_id: row.get(0)?,
+                    chat_id: row.get(1)?,
+                    chat_title: row.get(2)?,
+                    role: row.get(3)?,
+                    snippet: highlight_snippet(&snippet),
+                    created_at: row.get(5)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows {
+        out.push(r.map_err(|e| e.to_string())?);
+    }
+    Ok(out)
+}
@@ -0,0 +1,101 @@
+// src-tauri/src/commands/server.rs
+
+use std::sync::atomic::Ordering;
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::events::{MODEL_LOADING, MODEL_STOPPED};
+use crate::server::{await_server_startup, spawn_llama_server, stop_llama_server};
+use crate::state::LlamaServerManager;
+use crate::types::{AccelerationInfo, GetServerLogArgs, ServerLogLine, ServerStatus};
+
+/// Readiness, loaded model, download activity and uptime of llama-server,
+/// for a status panel.
+#[tauri::command]
+pub fn get_status(state: State<'_, LlamaServerManager>) -> Result<ServerStatus, String> {
+    server_status(&state)
+}
+
+pub fn server_status(state: &LlamaServerManager) -> Result<ServerStatus, String> {
+    let current_model_id = state.current_model_id.lock().map_err(|e| e.to_string())?.clone();
+    let supports_vision = state
+        .current_capabilities
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map_or(false, |c| c.vision);
+    let download_active = !state
+        .active_downloads
+        .lock()
+        .map_err(|e| e.to_string())?
+        .is_empty();
+    let uptime_secs = state
+        .server_started_at
+        .lock()
+        .map_err(|e| e.to_string())?
+        .and_then(|started| started.elapsed().ok())
+        .map(|elapsed| elapsed.as_secs());
+
+    Ok(ServerStatus {
+        is_ready: state.is_ready.load(Ordering::SeqCst),
+        current_model_id,
+        supports_vision,
+        download_active,
+        server_url: state.server_url(),
+        uptime_secs,
+    })
+}
+
+/// Stop llama-server to free its memory while the current model stays
+/// selected; `start_server` brings it back.
+#[tauri::command]
+pub fn stop_server(app: AppHandle, state: State<'_, LlamaServerManager>) -> Result<(), String> {
+    if stop_llama_server(&state)? {
+        println!("[llama-server] Stopped");
+    }
+    let _ = app.emit(MODEL_STOPPED, ());
+    Ok(())
+}
+
+/// Start llama-server with the currently selected model and settings,
+/// waiting until it is ready.
+#[tauri::command]
+pub async fn start_server(app: AppHandle, state: State<'_, LlamaServerManager>) -> Result<(), String> {
+    if state.process.lock().map_err(|e| e.to_string())?.is_some() {
+        return Err("llama-server is already running".to_string());
+    }
+    if state.current_model_id.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err("No model is selected".to_string());
+    }
+    let model_path = state.model_path.lock().map_err(|e| e.to_string())?.clone();
+    let mmproj_path = state.mmproj_path.lock().map_err(|e| e.to_string())?.clone();
+    if !model_path.exists() {
+        return Err(format!("Model file {} no longer exists", model_path.display()));
+    }
+
+    let _ = app.emit(MODEL_LOADING, ());
+    let spawned_at = spawn_llama_server(&app, &state, &model_path, mmproj_path.as_deref())?;
+    await_server_startup(&app, spawned_at).await
+}
+
+/// Return the most recent llama-server output lines (oldest first).
+/// Without `lines`, the whole buffer is returned.
+#[tauri::command]
+pub fn get_server_log(
+    args: GetServerLogArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Vec<ServerLogLine>, String> {
+    let log = state.server_log.lock().map_err(|e| e.to_string())?;
+    let take = args.lines.unwrap_or(log.len()).min(log.len());
+    Ok(log.iter().skip(log.len() - take).cloned().collect())
+}
+
+/// The compute backend detected from the running server's startup output, or
+/// None while the server is still starting (or not running).
+#[tauri::command]
+pub fn get_acceleration_info(
+    state: State<'_, LlamaServerManager>,
+) -> Result<Option<AccelerationInfo>, String> {
+    let acceleration = state.acceleration.lock().map_err(|e| e.to_string())?;
+    Ok(acceleration.clone())
+}
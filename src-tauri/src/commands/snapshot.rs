@@ -0,0 +1,137 @@
+// src-tauri/src/commands/snapshot.rs
+//
+// Snapshots save a chat's full message list so it can later be rolled back,
+// discarding anything said after the snapshot was taken.
+
+use rusqlite::params;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::{load_snapshot_messages, open_db, replace_chat_messages, unix_ms};
+use crate::events::CHATS_CHANGED;
+use crate::state::LlamaServerManager;
+use crate::types::{
+    ChatSnapshotInfo, ListSnapshotsArgs, SnapshotArgs, SnapshotChatArgs, SnapshotMessage,
+};
+
+/// Store the chat's current messages (all variants included) and return the snapshot id.
+#[tauri::command]
+pub fn snapshot_chat(
+    args: SnapshotChatArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<String, String> {
+    let conn = open_db(&state.db_path)?;
+
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM conversations WHERE id = ?1",
+            params![args.chat_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err(format!("Chat {} not found", args.chat_id));
+    }
+
+    let messages = load_snapshot_messages(&conn, &args.chat_id)?;
+    let messages_json = serde_json::to_string(&messages).map_err(|e| e.to_string())?;
+    let snapshot_id = uuid::Uuid::new_v4().to_string();
+
+    conn.execute(
+        "INSERT INTO snapshots (id, conversation_id, label, messages, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            snapshot_id,
+            args.chat_id,
+            args.label.unwrap_or_default(),
+            messages_json,
+            unix_ms()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    println!(
+        "[snapshot] Saved {} messages of {} as {}",
+        messages.len(),
+        args.chat_id,
+        snapshot_id
+    );
+    Ok(snapshot_id)
+}
+
+#[tauri::command]
+pub fn list_snapshots(
+    args: ListSnapshotsArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<Vec<ChatSnapshotInfo>, String> {
+    let conn = open_db(&state.db_path)?;
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, conversation_id, label, messages, created_at
+            FROM snapshots
+            WHERE conversation_id = ?1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![args.chat_id], |row| {
+            let messages: String = row.get(3)?;
+            let message_count = serde_json::from_str::<Vec<SnapshotMessage>>(&messages)
+                .map(|m| m.len())
+                .unwrap_or(0);
+            Ok(ChatSnapshotInfo {
+                id: row.get(0)?,
+                chat_id: row.get(1)?,
+                label: row.get(2)?,
+                message_count,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows {
+        out.push(r.map_err(|e| e.to_string())?);
+    }
+    Ok(out)
+}
+
+/// Roll the chat back to the snapshot, discarding messages added since.
+/// The snapshot itself is kept so it can be restored again.
+#[tauri::command]
+pub fn restore_snapshot(
+    args: SnapshotArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<String, String> {
+    let mut conn = open_db(&state.db_path)?;
+
+    let (chat_id, messages_json): (String, String) = conn
+        .query_row(
+            "SELECT conversation_id, messages FROM snapshots WHERE id = ?1",
+            params![args.snapshot_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| format!("Snapshot {} not found", args.snapshot_id))?;
+
+    let messages: Vec<SnapshotMessage> =
+        serde_json::from_str(&messages_json).map_err(|e| e.to_string())?;
+    replace_chat_messages(&mut conn, &chat_id, &messages)?;
+
+    println!("[snapshot] Restored {} to {}", chat_id, args.snapshot_id);
+    let _ = app.emit(CHATS_CHANGED, ());
+    Ok(chat_id)
+}
+
+#[tauri::command]
+pub fn delete_snapshot(args: SnapshotArgs, state: State<'_, LlamaServerManager>) -> Result<(), String> {
+    let conn = open_db(&state.db_path)?;
+    conn.execute(
+        "DELETE FROM snapshots WHERE id = ?1",
+        params![args.snapshot_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -1,19 +1,41 @@
 // src-tauri/src/commands/streaming.rs
 
-use std::sync::atomic::Ordering;
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 use std::time::Instant;
 
 use futures::StreamExt;
 use reqwest_eventsource::{Event, EventSource};
-use rusqlite::params;
+use rusqlite::{params, Connection};
+use serde_json::{json, Map};
 use tauri::{AppHandle, Emitter, State};
 
-use crate::db::{insert_message, open_db};
+use crate::commands::model::current_model_capabilities;
+use crate::db::{
+    begin_response_variant, insert_message, load_chat_summary, load_chat_system_prompt,
+    mark_message_truncated, open_db, reactivate_latest_variant, remember_chat_model,
+    save_chat_summary, save_draft, set_message_usage, set_message_variant,
+};
+use crate::events::{CHATS_CHANGED, CHAT_BEGIN, CHAT_DELTA, CHAT_END, CHAT_ERROR, MODEL_MISMATCH};
+use crate::http::http_client;
+use crate::images::{image_data_url, prepare_images};
+use crate::recording::{append_exchange, find_replay};
+use crate::response_cache::{
+    is_deterministic, lookup_cached_response, response_cache_key, store_cached_response,
+};
+use crate::server::report_oom_if_detected;
+use crate::settings::{BehaviorSettings, DebugSettings};
 use crate::state::LlamaServerManager;
+use crate::summarizer::summarize;
+use crate::think::ThinkTagParser;
 use crate::types::{
-    ChatBeginPayload, ChatDeltaPayload, ChatEndPayload, ChatMsg, ChatStreamArgs,
-    ImageUrlData, OpenAIContent, OpenAIContentPart, OpenAIMessage, OpenAIRequest,
-    OpenAIStreamResponse,
+    ChatBeginPayload, ChatDeltaPayload, ChatEndPayload, ChatErrorPayload, ChatMsg, ChatOnceArgs,
+    ChatStreamArgs, ImageUrlData, ModelMismatchPayload, OpenAIContent, OpenAIContentPart,
+    OpenAIMessage, OpenAINonStreamResponse, OpenAIRequest, OpenAIStreamOptions,
+    OpenAIStreamResponse, OpenAIUsage, RegenerateResponseArgs, WarmPromptArgs,
 };
 
 #[tauri::command]
@@ -24,105 +46,209 @@ pub async fn chat_stream(
 ) -> Result<(), String> {
     let chat_id = args.chat_id;
     let prompt = args.prompt;
-    let images = args.images;
 
     let start_time = Instant::now();
+    let images = prepare_attachments(&state, args.images).await?;
+    let generation = ActiveChatGuard::begin(&state, &chat_id)?;
 
-    // Reset cancellation flag
-    state.is_cancelled.store(false, Ordering::SeqCst);
+    let loaded_model_id = state.current_model_id.lock().map_err(|e| e.to_string())?.clone();
 
     // Save user message immediately
-    {
+    let chat_model_id = {
         let conn = open_db(&state.db_path)?;
         insert_message(&conn, &chat_id, "user", &prompt, "", &images, None)?;
+        remember_chat_model(&conn, &chat_id, loaded_model_id.as_deref())?
+    };
+    // A deferred chat only exists from this point on
+    let _ = app.emit(CHATS_CHANGED, ());
+
+    if let (Some(chat_model_id), Some(loaded_model_id)) = (chat_model_id, loaded_model_id) {
+        if chat_model_id != loaded_model_id {
+            println!(
+                "[chat_stream] Chat {} was started with {}, {} is loaded",
+                chat_id, chat_model_id, loaded_model_id
+            );
+            let _ = app.emit(
+                MODEL_MISMATCH,
+                ModelMismatchPayload {
+                    chat_id: chat_id.clone(),
+                    chat_model_id,
+                    loaded_model_id,
+                },
+            );
+        }
+    }
+
+    stream_assistant_reply(&app, &state, chat_id, None, start_time, &generation.cancel).await
+}
+
+/// Send a message and wait for the whole reply, without streaming. Both
+/// messages are stored like `chat_stream` does, but no events are emitted;
+/// returns the reply content.
+#[tauri::command]
+pub async fn chat_once(
+    args: ChatOnceArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<String, String> {
+    if !state.is_ready.load(Ordering::SeqCst) {
+        return Err("Model is not ready".to_string());
     }
 
-    // Load conversation history
-    let history_msgs = {
+    let start_time = Instant::now();
+    let images = prepare_attachments(&state, args.images).await?;
+    let _generation = ActiveChatGuard::begin(&state, &args.chat_id)?;
+    let loaded_model_id = state.current_model_id.lock().map_err(|e| e.to_string())?.clone();
+    let (system_prompt, behavior) = {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        (settings.defaults.system_prompt.clone(), settings.behavior.clone())
+    };
+
+    let messages = {
         let conn = open_db(&state.db_path)?;
+        insert_message(&conn, &args.chat_id, "user", &args.prompt, "", &images, None)?;
+        remember_chat_model(&conn, &args.chat_id, loaded_model_id.as_deref())?;
 
-        let mut stmt = conn
-            .prepare(
-                r#"
-                SELECT role, content, images
-                FROM messages
-                WHERE conversation_id = ?1
-                ORDER BY created_at ASC
-                "#,
-            )
-            .map_err(|e| e.to_string())?;
+        let system_prompt =
+            load_chat_system_prompt(&conn, &args.chat_id)?.unwrap_or(system_prompt);
+        let history_msgs = load_history(&conn, &args.chat_id)?;
+        build_chat_messages(&conn, &args.chat_id, system_prompt, &history_msgs, &behavior)?
+    };
 
-        let rows = stmt
-            .query_map(params![chat_id.clone()], |row| {
-                let images_json: String = row.get(2)?;
-                let images: Vec<String> =
-                    serde_json::from_str(&images_json).unwrap_or_else(|_| Vec::new());
-
-                Ok(ChatMsg {
-                    role: row.get(0)?,
-                    content: row.get(1)?,
-                    images,
-                })
-            })
-            .map_err(|e| e.to_string())?;
+    let supports_thinking = current_model_capabilities(&state).map_or(false, |c| c.thinking);
+    let request_body = OpenAIRequest {
+        model: state.served_model_name(),
+        messages,
+        stream: false,
+        max_tokens: behavior.max_tokens,
+        temperature: behavior.temperature,
+        top_p: behavior.top_p,
+        stop: behavior.stop.clone(),
+        stream_options: None,
+        cache_prompt: Some(true),
+        extra: behavior.chat_request_params(supports_thinking),
+    };
 
-        let mut msgs = Vec::new();
-        for r in rows {
-            msgs.push(r.map_err(|e| e.to_string())?);
+    let response = http_client(&state)?
+        .post(format!("{}/v1/chat/completions", state.server_url()))
+        .header("Content-Type", "application/json")
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP error: {}", response.status()));
+    }
+
+    let body: OpenAINonStreamResponse = response.json().await.map_err(|e| e.to_string())?;
+    let message = body
+        .choices
+        .into_iter()
+        .next()
+        .map(|c| c.message)
+        .ok_or_else(|| "Server returned no choices".to_string())?;
+    let mut content = message.content.unwrap_or_default();
+    let mut thinking = message.reasoning_content.unwrap_or_default();
+
+    if behavior.parse_think_tags {
+        let mut parser = ThinkTagParser::new();
+        let (mut answer, mut reasoning) = parser.feed(&content);
+        let (rest, rest_reasoning) = parser.finish();
+        answer.push_str(&rest);
+        reasoning.push_str(&rest_reasoning);
+        content = answer;
+        thinking.push_str(&reasoning);
+    }
+
+    let duration_ms = start_time.elapsed().as_millis() as i64;
+    {
+        let conn = open_db(&state.db_path)?;
+        let msg_id = insert_message(
+            &conn,
+            &args.chat_id,
+            "assistant",
+            &content,
+            &thinking,
+            &[],
+            Some(duration_ms),
+        )?;
+        if let Some(u) = body.usage {
+            set_message_usage(&conn, &msg_id, u.prompt_tokens, u.completion_tokens)?;
         }
-        msgs
+    }
+
+    println!("[chat_once] Replied to {} in {}ms", args.chat_id, duration_ms);
+    Ok(content)
+}
+
+/// Regenerate the latest assistant response. The previous response is kept
+/// as an inactive sibling variant (see `select_variant`) instead of being
+/// deleted, so the user can flip between candidates.
+#[tauri::command]
+pub async fn regenerate_response(
+    args: RegenerateResponseArgs,
+    app: AppHandle,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    let chat_id = args.chat_id;
+    let start_time = Instant::now();
+    let generation = ActiveChatGuard::begin(&state, &chat_id)?;
+
+    let variant = {
+        let conn = open_db(&state.db_path)?;
+        begin_response_variant(&conn, &chat_id)?
     };
 
-    // Get system prompt and max tokens from settings
-    let (system_prompt, max_tokens) = {
+    let result = stream_assistant_reply(
+        &app,
+        &state,
+        chat_id,
+        variant.clone(),
+        start_time,
+        &generation.cancel,
+    )
+    .await;
+
+    if let (Err(_), Some((group_id, _))) = (&result, &variant) {
+        let conn = open_db(&state.db_path)?;
+        reactivate_latest_variant(&conn, group_id)?;
+    }
+
+    result
+}
+
+/// Stream an assistant reply to the conversation's current (active) history
+/// and store it, optionally as a variant `(response_group_id, variant_index)`.
+/// Stops early once `cancel` is set.
+async fn stream_assistant_reply(
+    app: &AppHandle,
+    state: &LlamaServerManager,
+    chat_id: String,
+    variant: Option<(String, i64)>,
+    start_time: Instant,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    // Global system prompt, sampling and parsing options from settings
+    let (system_prompt, behavior, debug) = {
         let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
         (
             settings.defaults.system_prompt.clone(),
-            settings.behavior.max_tokens,
+            settings.behavior.clone(),
+            settings.debug.clone(),
         )
     };
 
-    // Build OpenAI-format messages
-    let mut openai_messages: Vec<OpenAIMessage> = vec![OpenAIMessage {
-        role: "system".to_string(),
-        content: OpenAIContent::Text(system_prompt),
-    }];
-
-    // Add recent history (last 20 turns)
-    let recent = if history_msgs.len() > 20 {
-        &history_msgs[history_msgs.len() - 20..]
-    } else {
-        &history_msgs[..]
+    // Load conversation history, summarizing what no longer fits
+    let openai_messages = {
+        let conn = open_db(&state.db_path)?;
+        let system_prompt = load_chat_system_prompt(&conn, &chat_id)?.unwrap_or(system_prompt);
+        let history_msgs = load_history(&conn, &chat_id)?;
+        build_chat_messages(&conn, &chat_id, system_prompt, &history_msgs, &behavior)?
     };
 
-    for msg in recent {
-        let content = if msg.images.is_empty() {
-            OpenAIContent::Text(msg.content.clone())
-        } else {
-            let mut parts: Vec<OpenAIContentPart> = vec![OpenAIContentPart::Text {
-                text: msg.content.clone(),
-            }];
-
-            for img_base64 in &msg.images {
-                parts.push(OpenAIContentPart::ImageUrl {
-                    image_url: ImageUrlData {
-                        url: format!("data:image/jpeg;base64,{}", img_base64),
-                    },
-                });
-            }
-
-            OpenAIContent::Parts(parts)
-        };
-
-        openai_messages.push(OpenAIMessage {
-            role: msg.role.clone(),
-            content,
-        });
-    }
-
     // Emit stream begin
     app.emit(
-        "chat:begin",
+        CHAT_BEGIN,
         ChatBeginPayload {
             chat_id: chat_id.clone(),
         },
@@ -130,40 +256,94 @@ pub async fn chat_stream(
     .map_err(|e| e.to_string())?;
 
     // Make streaming request to llama-server
-    let client = reqwest::Client::new();
+    let client = http_client(state)?;
+    let supports_thinking = current_model_capabilities(state).map_or(false, |c| c.thinking);
     let request_body = OpenAIRequest {
-        model: "qwen3-vl".to_string(),
+        model: state.served_model_name(),
         messages: openai_messages,
         stream: true,
-        max_tokens,
+        max_tokens: behavior.max_tokens,
+        temperature: behavior.temperature,
+        top_p: behavior.top_p,
+        stop: behavior.stop.clone(),
+        stream_options: Some(OpenAIStreamOptions { include_usage: true }),
+        cache_prompt: Some(true),
+        extra: behavior.chat_request_params(supports_thinking),
     };
 
-    let request_builder = client
-        .post(format!("{}/v1/chat/completions", state.server_url))
-        .header("Content-Type", "application/json")
-        .json(&request_body);
-
-    let mut es = EventSource::new(request_builder).map_err(|e| e.to_string())?;
+    // Identical deterministic requests can be answered from the response cache
+    let cache_key = if behavior.cache_deterministic_responses && is_deterministic(&request_body) {
+        let model_id = state.current_model_id.lock().map_err(|e| e.to_string())?.clone();
+        Some(response_cache_key(model_id.as_deref(), &request_body)?)
+    } else {
+        None
+    };
+    let cached = match cache_key {
+        Some(ref key) => {
+            let conn = open_db(&state.db_path)?;
+            lookup_cached_response(&conn, key)?
+        }
+        None => None,
+    };
+    let from_cache = cached.is_some();
+
+    let mut source = if let Some((content, thinking)) = cached {
+        println!("[cache] Serving cached deterministic response");
+        ResponseSource::Replay(cached_chunks(content, thinking).into_iter())
+    } else if debug.replay_responses {
+        let path = recording_path(&debug)?;
+        println!("[replay] Serving recorded response from {}", path.display());
+        ResponseSource::Replay(find_replay(&path, &request_body)?.into_iter())
+    } else {
+        let request_builder = client
+            .post(format!("{}/v1/chat/completions", state.server_url()))
+            .header("Content-Type", "application/json")
+            .json(&request_body);
+        ResponseSource::Live(EventSource::new(request_builder).map_err(|e| e.to_string())?)
+    };
+    let mut recorded_chunks: Vec<String> = Vec::new();
     let mut full_response_content = String::new();
     let mut full_response_thinking = String::new();
+    let mut usage: Option<OpenAIUsage> = None;
+    let mut stream_error: Option<String> = None;
+    let mut think_parser = behavior.parse_think_tags.then(|| {
+        if supports_thinking {
+            ThinkTagParser::expecting_reasoning()
+        } else {
+            ThinkTagParser::new()
+        }
+    });
 
-    while let Some(event) = es.next().await {
-        if state.is_cancelled.load(Ordering::SeqCst) {
-            es.close();
+    while let Some(event) = source.next_data().await {
+        if cancel.load(Ordering::SeqCst) {
+            source.close();
             break;
         }
 
         match event {
-            Ok(Event::Open) => {}
-            Ok(Event::Message(msg)) => {
-                if msg.data == "[DONE]" {
+            Ok(data) => {
+                if debug.record_requests {
+                    recorded_chunks.push(data.clone());
+                }
+
+                if data == "[DONE]" {
                     break;
                 }
 
-                if let Ok(parsed) = serde_json::from_str::<OpenAIStreamResponse>(&msg.data) {
+                if let Ok(parsed) = serde_json::from_str::<OpenAIStreamResponse>(&data) {
+                    if parsed.usage.is_some() {
+                        usage = parsed.usage;
+                    }
                     if let Some(choice) = parsed.choices.first() {
-                        let content_delta = choice.delta.content.clone().unwrap_or_default();
-                        let reasoning_delta = choice.delta.reasoning_content.clone().unwrap_or_default();
+                        let mut content_delta = choice.delta.content.clone().unwrap_or_default();
+                        let mut reasoning_delta = choice.delta.reasoning_content.clone().unwrap_or_default();
+
+                        // Route inline <think> blocks to the reasoning stream
+                        if let Some(parser) = think_parser.as_mut() {
+                            let (content, thinking) = parser.feed(&content_delta);
+                            content_delta = content;
+                            reasoning_delta.push_str(&thinking);
+                        }
 
                         if !content_delta.is_empty() {
                             full_response_content.push_str(&content_delta);
@@ -175,7 +355,7 @@ pub async fn chat_stream(
                         }
 
                         app.emit(
-                            "chat:delta",
+                            CHAT_DELTA,
                             ChatDeltaPayload {
                                 chat_id: chat_id.clone(),
                                 delta: content_delta,
@@ -187,18 +367,94 @@ pub async fn chat_stream(
                 }
             }
             Err(e) => {
-                eprintln!("[SSE Error] {:?}", e);
+                eprintln!("[SSE Error] {}", e);
+                report_oom_if_detected(app);
+                let _ = app.emit(
+                    CHAT_ERROR,
+                    ChatErrorPayload {
+                        chat_id: chat_id.clone(),
+                        error: e.clone(),
+                    },
+                );
+                stream_error = Some(e);
                 break;
             }
         }
     }
 
+    // Flush a partial tag held back by the parser
+    if let Some(mut parser) = think_parser {
+        let (content_delta, reasoning_delta) = parser.finish();
+        if !content_delta.is_empty() || !reasoning_delta.is_empty() {
+            full_response_content.push_str(&content_delta);
+            full_response_thinking.push_str(&reasoning_delta);
+            app.emit(
+                CHAT_DELTA,
+                ChatDeltaPayload {
+                    chat_id: chat_id.clone(),
+                    delta: content_delta,
+                    reasoning_delta,
+                },
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    if let Some(ref key) = cache_key {
+        if !from_cache && stream_error.is_none() && !cancel.load(Ordering::SeqCst) {
+            let conn = open_db(&state.db_path)?;
+            store_cached_response(&conn, key, &full_response_content, &full_response_thinking)?;
+        }
+    }
+
+    if debug.record_requests && !debug.replay_responses {
+        let path = recording_path(&debug)?;
+        if let Err(e) = append_exchange(&path, &request_body, recorded_chunks) {
+            eprintln!("[record] {}", e);
+        }
+    }
+
     let duration_ms = start_time.elapsed().as_millis() as i64;
 
+    // Cancelled via cancel_to_draft: keep the partial text as an editable
+    // draft instead of committing it as an assistant message
+    let keep_as_draft = cancel.load(Ordering::SeqCst)
+        && state
+            .draft_requests
+            .lock()
+            .map(|requests| requests.contains(&chat_id))
+            .unwrap_or(false);
+
+    if keep_as_draft {
+        {
+            let conn = open_db(&state.db_path)?;
+            save_draft(&conn, &chat_id, &full_response_content, &full_response_thinking)?;
+            if let Some((ref group_id, _)) = variant {
+                reactivate_latest_variant(&conn, group_id)?;
+            }
+        }
+        if let Ok(mut requests) = state.draft_requests.lock() {
+            requests.remove(&chat_id);
+        }
+        println!("[chat_stream] Saved partial response as draft for {}", chat_id);
+
+        app.emit(
+            CHAT_END,
+            ChatEndPayload {
+                chat_id: chat_id.clone(),
+                duration_ms,
+                prompt_tokens: usage.map(|u| u.prompt_tokens),
+                completion_tokens: usage.map(|u| u.completion_tokens),
+            },
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
     // Save assistant response
     {
         let conn = open_db(&state.db_path)?;
-        insert_message(
+        let msg_id = insert_message(
             &conn,
             &chat_id,
             "assistant",
@@ -207,19 +463,435 @@ pub async fn chat_stream(
             &[],
             Some(duration_ms),
         )?;
+        if let Some((group_id, variant_index)) = variant {
+            set_message_variant(&conn, &msg_id, &group_id, variant_index)?;
+        }
+        if let Some(u) = usage {
+            set_message_usage(&conn, &msg_id, u.prompt_tokens, u.completion_tokens)?;
+        }
+        if stream_error.is_some() {
+            mark_message_truncated(&conn, &msg_id)?;
+        }
     }
 
     // Emit stream end
     app.emit(
-        "chat:end",
+        CHAT_END,
         ChatEndPayload {
             chat_id: chat_id.clone(),
             duration_ms,
+            prompt_tokens: usage.map(|u| u.prompt_tokens),
+            completion_tokens: usage.map(|u| u.completion_tokens),
         },
     )
     .map_err(|e| e.to_string())?;
 
-    let _ = app.emit("chats:changed", ());
+    let _ = app.emit(CHATS_CHANGED, ());
 
     Ok(())
 }
+
+/// Validate and downscale a message's images before they are stored and sent.
+/// Fails when the current model can't take images.
+async fn prepare_attachments(
+    state: &LlamaServerManager,
+    images: Vec<String>,
+) -> Result<Vec<String>, String> {
+    if images.is_empty() {
+        return Ok(images);
+    }
+    if !current_model_capabilities(state).map_or(false, |c| c.vision) {
+        return Err("The current model doesn't support images — switch to a vision model.".to_string());
+    }
+    let max_dimension = {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        settings.behavior.max_image_dimension
+    };
+    tokio::task::spawn_blocking(move || prepare_images(images, max_dimension))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Registers a chat's cancel token for as long as it lives, so concurrent
+/// requests can't interleave their writes to one conversation and
+/// `cancel_generation` can stop just this chat.
+struct ActiveChatGuard<'a> {
+    state: &'a LlamaServerManager,
+    chat_id: String,
+    cancel: Arc<AtomicBool>,
+}
+
+impl<'a> ActiveChatGuard<'a> {
+    fn begin(state: &'a LlamaServerManager, chat_id: &str) -> Result<Self, String> {
+        let mut generations = state.generations.lock().map_err(|e| e.to_string())?;
+        if generations.contains_key(chat_id) {
+            return Err("A reply is already being generated for this chat".to_string());
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        generations.insert(chat_id.to_string(), cancel.clone());
+        Ok(Self {
+            state,
+            chat_id: chat_id.to_string(),
+            cancel,
+        })
+    }
+}
+
+impl Drop for ActiveChatGuard<'_> {
+    fn drop(&mut self) {
+        // A draft request the stream didn't get to (it failed first) must not
+        // turn the chat's next cancelled generation into a draft
+        if let Ok(mut requests) = self.state.draft_requests.lock() {
+            requests.remove(&self.chat_id);
+        }
+        if let Ok(mut generations) = self.state.generations.lock() {
+            generations.remove(&self.chat_id);
+        }
+    }
+}
+
+/// Where SSE `data:` payloads come from: the live server or a recording.
+enum ResponseSource {
+    Live(EventSource),
+    Replay(std::vec::IntoIter<String>),
+}
+
+impl ResponseSource {
+    async fn next_data(&mut self) -> Option<Result<String, String>> {
+        match self {
+            ResponseSource::Live(es) => loop {
+                match es.next().await? {
+                    Ok(Event::Open) => continue,
+                    Ok(Event::Message(msg)) => return Some(Ok(msg.data)),
+                    Err(e) => return Some(Err(format!("{:?}", e))),
+                }
+            },
+            ResponseSource::Replay(chunks) => chunks.next().map(Ok),
+        }
+    }
+
+    fn close(&mut self) {
+        if let ResponseSource::Live(es) = self {
+            es.close();
+        }
+    }
+}
+
+/// A cached answer as the SSE payloads `stream_assistant_reply` consumes.
+fn cached_chunks(content: String, thinking: String) -> Vec<String> {
+    let delta = json!({
+        "choices": [{ "delta": { "content": content, "reasoning_content": thinking } }]
+    });
+    vec![delta.to_string(), "[DONE]".to_string()]
+}
+
+fn recording_path(debug: &DebugSettings) -> Result<PathBuf, String> {
+    debug
+        .recording_path
+        .as_ref()
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| "No recording path is set".to_string())
+}
+
+/// Pre-send a conversation's existing messages so llama-server has the shared
+/// prefix in its KV cache before the next user message arrives.
+///
+/// The server keeps the processed prompt in the slot's KV cache
+/// (`cache_prompt`), so the following `chat_stream` only has to evaluate the
+/// new turn. The tradeoff is memory: the cached prefix occupies the slot's
+/// context until it is evicted by a different conversation, and it is bounded
+/// by `context_length` just like a normal request.
+#[tauri::command]
+pub async fn warm_prompt(
+    args: WarmPromptArgs,
+    state: State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    if !state.is_ready.load(Ordering::SeqCst) {
+        return Err("Model is not ready".to_string());
+    }
+
+    // Must build the same prefix chat_stream will send, or the cache misses
+    let (system_prompt, behavior) = {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        (settings.defaults.system_prompt.clone(), settings.behavior.clone())
+    };
+
+    let (history_len, messages) = {
+        let conn = open_db(&state.db_path)?;
+        let system_prompt =
+            load_chat_system_prompt(&conn, &args.chat_id)?.unwrap_or(system_prompt);
+        let history_msgs = load_history(&conn, &args.chat_id)?;
+        let messages =
+            build_chat_messages(&conn, &args.chat_id, system_prompt, &history_msgs, &behavior)?;
+        (history_msgs.len(), messages)
+    };
+
+    let request_body = OpenAIRequest {
+        model: state.served_model_name(),
+        messages,
+        stream: false,
+        // Only the prompt evaluation matters; generate as little as possible
+        max_tokens: 1,
+        temperature: None,
+        top_p: None,
+        stop: None,
+        stream_options: None,
+        cache_prompt: Some(true),
+        extra: Map::new(),
+    };
+
+    let start_time = Instant::now();
+    let response = http_client(&state)?
+        .post(format!("{}/v1/chat/completions", state.server_url()))
+        .header("Content-Type", "application/json")
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP error: {}", response.status()));
+    }
+
+    println!(
+        "[warm_prompt] Cached {} messages for {} in {}ms",
+        history_len,
+        args.chat_id,
+        start_time.elapsed().as_millis()
+    );
+
+    Ok(())
+}
+
+/// Load a conversation's messages in chronological order, skipping
+/// inactive response variants.
+pub fn load_history(conn: &Connection, chat_id: &str) -> Result<Vec<ChatMsg>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT role, content, thinking, images
+            FROM messages
+            WHERE conversation_id = ?1 AND is_active = 1
+            ORDER BY created_at ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![chat_id], |row| {
+            let images_json: String = row.get(3)?;
+            let images: Vec<String> =
+                serde_json::from_str(&images_json).unwrap_or_else(|_| Vec::new());
+
+            Ok(ChatMsg {
+                role: row.get(0)?,
+                content: row.get(1)?,
+                thinking: row.get(2)?,
+                images,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut msgs = Vec::new();
+    for r in rows {
+        msgs.push(r.map_err(|e| e.to_string())?);
+    }
+    Ok(msgs)
+}
+
+/// Rough tokens per image for vision projectors; real cost depends on the
+/// model and image size, this just keeps image-heavy history in check.
+const IMAGE_TOKEN_ESTIMATE: usize = 768;
+/// Per-message overhead of the chat template (role markers, separators).
+const MESSAGE_TOKEN_OVERHEAD: usize = 4;
+/// Context set aside for the summary of trimmed messages.
+const SUMMARY_TOKEN_RESERVE: usize = 256;
+const SUMMARY_SENTENCES: usize = 5;
+
+/// Cheap token estimate (~4 characters per token) used to fit history into
+/// the context window without loading a tokenizer.
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4) + MESSAGE_TOKEN_OVERHEAD
+}
+
+/// Convert stored history into OpenAI-format messages, prefixed with the
+/// system prompt and trimmed to fit the context window. Messages that don't
+/// fit are dropped; see `build_chat_messages` to summarize them instead.
+pub fn build_openai_messages(
+    system_prompt: String,
+    history_msgs: &[ChatMsg],
+    behavior: &BehaviorSettings,
+) -> Vec<OpenAIMessage> {
+    let texts = replay_texts(history_msgs, behavior);
+    let keep_from = history_start(history_msgs, &texts, context_budget(&system_prompt, behavior));
+    if keep_from > 0 {
+        println!("[context] Dropped {} of {} messages", keep_from, history_msgs.len());
+    }
+    assemble_messages(system_prompt, history_msgs, texts, keep_from)
+}
+
+/// Like `build_openai_messages`, but messages that no longer fit are folded
+/// into the chat's running summary (`conversations.summary`), which is
+/// appended to the system prompt as "Earlier in this conversation: ...".
+/// The summary is extended incrementally as more messages scroll out.
+pub fn build_chat_messages(
+    conn: &Connection,
+    chat_id: &str,
+    system_prompt: String,
+    history_msgs: &[ChatMsg],
+    behavior: &BehaviorSettings,
+) -> Result<Vec<OpenAIMessage>, String> {
+    let texts = replay_texts(history_msgs, behavior);
+    let budget = context_budget(&system_prompt, behavior);
+
+    let mut keep_from = history_start(history_msgs, &texts, budget);
+    if keep_from == 0 {
+        return Ok(assemble_messages(system_prompt, history_msgs, texts, 0));
+    }
+    keep_from = history_start(history_msgs, &texts, budget.saturating_sub(SUMMARY_TOKEN_RESERVE));
+    println!("[context] Summarizing {} of {} messages", keep_from, history_msgs.len());
+
+    let (previous, covered) = load_chat_summary(conn, chat_id)?;
+    let summary = if covered == keep_from && !previous.is_empty() {
+        previous
+    } else {
+        // Extend the summary when more messages dropped out; start over if
+        // fewer did (e.g. a larger context length or deleted messages)
+        let (base, from) = if covered > 0 && covered < keep_from {
+            (previous, covered)
+        } else {
+            (String::new(), 0)
+        };
+        let mut text = base;
+        for t in &texts[from..keep_from] {
+            text.push('\n');
+            text.push_str(t);
+        }
+        let summary = summarize(text.trim(), SUMMARY_SENTENCES);
+        save_chat_summary(conn, chat_id, &summary, keep_from)?;
+        summary
+    };
+
+    let system_prompt = format!("{}\n\nEarlier in this conversation: {}", system_prompt, summary);
+    Ok(assemble_messages(system_prompt, history_msgs, texts, keep_from))
+}
+
+/// Tokens left for history once the reply and system prompt are accounted for.
+fn context_budget(system_prompt: &str, behavior: &BehaviorSettings) -> usize {
+    (behavior.context_length as usize)
+        .saturating_sub(behavior.max_tokens as usize)
+        .saturating_sub(estimate_tokens(system_prompt))
+}
+
+/// The text each message is replayed as. With `include_prior_thinking`,
+/// earlier assistant turns carry their stored reasoning in a leading
+/// `<think>` block, the way the model produced it; otherwise only the
+/// visible answer is replayed, which saves context.
+fn replay_texts(history_msgs: &[ChatMsg], behavior: &BehaviorSettings) -> Vec<String> {
+    history_msgs
+        .iter()
+        .map(|msg| {
+            if behavior.include_prior_thinking && msg.role == "assistant" && !msg.thinking.is_empty() {
+                format!("<think>\n{}\n</think>\n\n{}", msg.thinking.trim(), msg.content)
+            } else {
+                msg.content.clone()
+            }
+        })
+        .collect()
+}
+
+/// Index of the oldest message that still fits in `budget`, walking back
+/// from the newest. The newest message is always kept.
+fn history_start(history_msgs: &[ChatMsg], texts: &[String], budget: usize) -> usize {
+    let mut used = 0;
+    let mut keep_from = history_msgs.len();
+    for (i, (msg, text)) in history_msgs.iter().zip(texts).enumerate().rev() {
+        let cost = estimate_tokens(text) + msg.images.len() * IMAGE_TOKEN_ESTIMATE;
+        if used + cost > budget && keep_from < history_msgs.len() {
+            break;
+        }
+        used += cost;
+        keep_from = i;
+    }
+    keep_from
+}
+
+fn assemble_messages(
+    system_prompt: String,
+    history_msgs: &[ChatMsg],
+    texts: Vec<String>,
+    keep_from: usize,
+) -> Vec<OpenAIMessage> {
+    let mut openai_messages: Vec<OpenAIMessage> = vec![OpenAIMessage {
+        role: "system".to_string(),
+        content: OpenAIContent::Text(system_prompt),
+    }];
+
+    for (msg, text) in history_msgs[keep_from..].iter().zip(texts.into_iter().skip(keep_from)) {
+        let content = if msg.images.is_empty() {
+            OpenAIContent::Text(text)
+        } else {
+            let mut parts: Vec<OpenAIContentPart> = vec![OpenAIContentPart::Text { text }];
+
+            for img_base64 in &msg.images {
+                parts.push(OpenAIContentPart::ImageUrl {
+                    image_url: ImageUrlData {
+                        url: image_data_url(img_base64),
+                    },
+                });
+            }
+
+            OpenAIContent::Parts(parts)
+        };
+
+        openai_messages.push(OpenAIMessage {
+            role: msg.role.clone(),
+            content,
+        });
+    }
+
+    openai_messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(role: &str, content: &str, thinking: &str) -> ChatMsg {
+        ChatMsg {
+            role: role.to_string(),
+            content: content.to_string(),
+            thinking: thinking.to_string(),
+            images: Vec::new(),
+        }
+    }
+
+    fn behavior(include_prior_thinking: bool) -> BehaviorSettings {
+        BehaviorSettings {
+            include_prior_thinking,
+            ..BehaviorSettings::default()
+        }
+    }
+
+    #[test]
+    fn prior_thinking_off_replays_content_only() {
+        let history = [msg("user", "question", ""), msg("assistant", "answer", "reasoning")];
+        assert_eq!(replay_texts(&history, &behavior(false)), ["question", "answer"]);
+    }
+
+    #[test]
+    fn prior_thinking_on_prepends_reasoning_to_assistant_turns() {
+        let history = [msg("assistant", "answer", "  reasoning\n")];
+        assert_eq!(
+            replay_texts(&history, &behavior(true)),
+            ["<think>\nreasoning\n</think>\n\nanswer"]
+        );
+    }
+
+    #[test]
+    fn prior_thinking_on_leaves_user_turns_and_empty_thinking_alone() {
+        let history = [msg("user", "question", "stray"), msg("assistant", "answer", "")];
+        assert_eq!(replay_texts(&history, &behavior(true)), ["question", "answer"]);
+    }
+}
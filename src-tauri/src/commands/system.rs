@@ -0,0 +1,10 @@
+// src-tauri/src/commands/system.rs
+
+use crate::system::system_info;
+use crate::types::SystemInfo;
+
+/// Total/available RAM and CPU core count of this machine.
+#[tauri::command]
+pub fn get_system_info() -> Result<SystemInfo, String> {
+    Ok(system_info())
+}
@@ -1,12 +1,20 @@
 // src-tauri/src/db.rs
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use tauri::AppHandle;
 use tauri::Manager;
 
+use crate::types::{ChatDraft, SnapshotMessage};
+
+/// Stored in `PRAGMA user_version` by `init_db`. Bump it when a migration
+/// makes the schema unreadable by older builds; imports from newer versions
+/// are refused. Databases from before versioning report 0.
+pub const SCHEMA_VERSION: i64 = 1;
+
 pub fn unix_ms() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -14,12 +22,21 @@ pub fn unix_ms() -> i64 {
         .as_millis() as i64
 }
 
+/// Open the chat database with the app's pragmas.
+///
+/// Foreign keys are enforced on every connection, so deleting a conversation
+/// anywhere in the app (`delete_chat`, `delete_empty_chats`, ...) also removes
+/// its messages, drafts, snapshots and tags through `ON DELETE CASCADE`, and
+/// rows can't be inserted for a conversation that doesn't exist.
 pub fn open_db(path: &Path) -> Result<Connection, String> {
     let conn = Connection::open(path).map_err(|e| e.to_string())?;
     conn.pragma_update(None, "journal_mode", "WAL")
         .map_err(|e| e.to_string())?;
     conn.pragma_update(None, "synchronous", "NORMAL")
         .map_err(|e| e.to_string())?;
+    // Off by default in SQLite
+    conn.pragma_update(None, "foreign_keys", "ON")
+        .map_err(|e| e.to_string())?;
     conn.busy_timeout(Duration::from_millis(2000))
         .map_err(|e| e.to_string())?;
     Ok(conn)
@@ -49,6 +66,39 @@ pub fn init_db(conn: &Connection) -> Result<(), String> {
 
         CREATE INDEX IF NOT EXISTS idx_messages_conv_created
             ON messages(conversation_id, created_at);
+
+        CREATE TABLE IF NOT EXISTS drafts (
+            conversation_id TEXT PRIMARY KEY,
+            content         TEXT NOT NULL,
+            thinking        TEXT NOT NULL DEFAULT '',
+            updated_at      INTEGER NOT NULL,
+            FOREIGN KEY(conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS response_cache (
+            hash        TEXT PRIMARY KEY,
+            content     TEXT NOT NULL,
+            thinking    TEXT NOT NULL DEFAULT '',
+            created_at  INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS snapshots (
+            id              TEXT PRIMARY KEY,
+            conversation_id TEXT NOT NULL,
+            label           TEXT NOT NULL DEFAULT '',
+            messages        TEXT NOT NULL,
+            created_at      INTEGER NOT NULL,
+            FOREIGN KEY(conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS tags (
+            conversation_id TEXT NOT NULL,
+            tag             TEXT NOT NULL COLLATE NOCASE,
+            PRIMARY KEY(conversation_id, tag),
+            FOREIGN KEY(conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_tags_tag ON tags(tag);
         "#,
     )
     .map_err(|e| e.to_string())?;
@@ -98,6 +148,119 @@ pub fn init_db(conn: &Connection) -> Result<(), String> {
             .map_err(|e| e.to_string())?;
     }
 
+    // Migration: response variants (regenerated answers kept as siblings)
+    add_column_if_missing(conn, "messages", "response_group_id", "TEXT")?;
+    add_column_if_missing(conn, "messages", "variant_index", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "messages", "is_active", "INTEGER NOT NULL DEFAULT 1")?;
+
+    // Migration: token usage reported by the server (NULL when it didn't)
+    add_column_if_missing(conn, "messages", "prompt_tokens", "INTEGER")?;
+    add_column_if_missing(conn, "messages", "completion_tokens", "INTEGER")?;
+
+    // Migration: replies cut off by a stream error
+    add_column_if_missing(conn, "messages", "truncated", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // Migration: how many leading history messages `summary` covers
+    add_column_if_missing(conn, "conversations", "summary_message_count", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // Migration: archived chats are hidden from the list but kept
+    add_column_if_missing(conn, "conversations", "archived", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // Migration: pinned chats are listed first; existing chats start unpinned
+    add_column_if_missing(conn, "conversations", "pinned", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // Migration: per-chat system prompt; NULL falls back to the global one
+    add_column_if_missing(conn, "conversations", "system_prompt", "TEXT")?;
+
+    // Migration: the model a chat was started with (NULL until one is known)
+    add_column_if_missing(conn, "conversations", "model_id", "TEXT")?;
+
+    // Migration: titles are generated at most once per chat
+    add_column_if_missing(conn, "conversations", "title_generated", "INTEGER NOT NULL DEFAULT 0")?;
+
+    init_message_search(conn)?;
+
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Full-text index over message content, kept in sync by triggers.
+///
+/// The index stores its own copy of the text keyed by message id rather than
+/// using `messages` as external content, since `messages` has no INTEGER
+/// PRIMARY KEY and its implicit rowids may change on VACUUM.
+fn init_message_search(conn: &Connection) -> Result<(), String> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'messages_fts'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
+
+    conn.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+            message_id UNINDEXED,
+            content,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+            INSERT INTO messages_fts (message_id, content) VALUES (new.id, new.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+            DELETE FROM messages_fts WHERE message_id = old.id;
+            INSERT INTO messages_fts (message_id, content) VALUES (new.id, new.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+            DELETE FROM messages_fts WHERE message_id = old.id;
+        END;
+        "#,
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Backfill messages written before the index existed
+    if !exists {
+        let indexed = conn
+            .execute(
+                "INSERT INTO messages_fts (message_id, content) SELECT id, content FROM messages",
+                [],
+            )
+            .map_err(|e| e.to_string())?;
+        println!("[db] Indexed {} existing messages for search", indexed);
+    }
+
+    Ok(())
+}
+
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), String> {
+    let exists: bool = conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?1",
+                table
+            ),
+            params![column],
+            |row| row.get(0),
+        )
+        .unwrap_or(false);
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    }
     Ok(())
 }
 
@@ -109,11 +272,20 @@ pub fn insert_message(
     thinking: &str,
     images: &[String],
     duration_ms: Option<i64>,
-) -> Result<(), String> {
+) -> Result<String, String> {
     let now = unix_ms();
     let msg_id = uuid::Uuid::new_v4().to_string();
     let images_json = serde_json::to_string(images).unwrap_or_else(|_| "[]".to_string());
 
+    // Creates the conversation on first message for chats from a deferred `new_chat`
+    conn.execute(
+        "INSERT INTO conversations (id, title, summary, created_at, updated_at)
+         VALUES (?1, 'New chat', '', ?2, ?2)
+         ON CONFLICT(id) DO UPDATE SET updated_at = excluded.updated_at",
+        params![chat_id, now],
+    )
+    .map_err(|e| e.to_string())?;
+
     conn.execute(
         "INSERT INTO messages (id, conversation_id, role, content, thinking, images, created_at, duration_ms)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
@@ -130,17 +302,504 @@ pub fn insert_message(
     )
     .map_err(|e| e.to_string())?;
 
+    Ok(msg_id)
+}
+
+/// Prepare to regenerate the latest assistant response of a chat as a new
+/// variant. The existing response (and any earlier variants) are kept but
+/// deactivated so they drop out of the context.
+/// Returns `(response_group_id, variant_index)` for the new response, or
+/// `None` when the chat doesn't end with an assistant message.
+pub fn begin_response_variant(conn: &Connection, chat_id: &str) -> Result<Option<(String, i64)>, String> {
+    let last: Option<(String, String, Option<String>)> = conn
+        .query_row(
+            r#"
+            SELECT id, role, response_group_id FROM messages
+            WHERE conversation_id = ?1 AND is_active = 1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            params![chat_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok();
+
+    let (msg_id, group_id) = match last {
+        Some((id, role, group)) if role == "assistant" => {
+            let group_id = group.unwrap_or_else(|| id.clone());
+            (id, group_id)
+        }
+        _ => return Ok(None),
+    };
+
+    conn.execute(
+        "UPDATE messages SET response_group_id = ?1 WHERE id = ?2 AND response_group_id IS NULL",
+        params![group_id, msg_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let next_index: i64 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(variant_index), 0) + 1 FROM messages WHERE response_group_id = ?1",
+            params![group_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "UPDATE messages SET is_active = 0 WHERE response_group_id = ?1",
+        params![group_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(Some((group_id, next_index)))
+}
+
+pub fn set_message_variant(
+    conn: &Connection,
+    msg_id: &str,
+    group_id: &str,
+    variant_index: i64,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE messages SET response_group_id = ?1, variant_index = ?2 WHERE id = ?3",
+        params![group_id, variant_index, msg_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The running summary of a chat's oldest messages and how many of its
+/// (active) messages it covers.
+pub fn load_chat_summary(conn: &Connection, chat_id: &str) -> Result<(String, usize), String> {
+    conn.query_row(
+        "SELECT summary, summary_message_count FROM conversations WHERE id = ?1",
+        params![chat_id],
+        |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)),
+    )
+    .optional()
+    .map(|summary| summary.unwrap_or_default())
+    .map_err(|e| e.to_string())
+}
+
+pub fn save_chat_summary(conn: &Connection, chat_id: &str, summary: &str, message_count: usize) -> Result<(), String> {
+    conn.execute(
+        "UPDATE conversations SET summary = ?1, summary_message_count = ?2 WHERE id = ?3",
+        params![summary, message_count as i64, chat_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The chat's own system prompt, if it overrides the global one.
+pub fn load_chat_system_prompt(conn: &Connection, chat_id: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT system_prompt FROM conversations WHERE id = ?1",
+        params![chat_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(|prompt| prompt.flatten())
+    .map_err(|e| e.to_string())
+}
+
+/// Set or clear (`None`) a chat's system prompt. Returns false if the chat
+/// doesn't exist.
+pub fn set_chat_system_prompt(conn: &Connection, chat_id: &str, prompt: Option<&str>) -> Result<bool, String> {
+    let changed = conn
+        .execute(
+            "UPDATE conversations SET system_prompt = ?1 WHERE id = ?2",
+            params![prompt, chat_id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(changed > 0)
+}
+
+/// Record `model_id` as the chat's model unless it already has one, and
+/// return the chat's model.
+pub fn remember_chat_model(
+    conn: &Connection,
+    chat_id: &str,
+    model_id: Option<&str>,
+) -> Result<Option<String>, String> {
+    if let Some(model_id) = model_id {
+        conn.execute(
+            "UPDATE conversations SET model_id = ?1 WHERE id = ?2 AND model_id IS NULL",
+            params![model_id, chat_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    conn.query_row(
+        "SELECT model_id FROM conversations WHERE id = ?1",
+        params![chat_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(|model| model.flatten())
+    .map_err(|e| e.to_string())
+}
+
+pub fn mark_message_truncated(conn: &Connection, msg_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE messages SET truncated = 1 WHERE id = ?1",
+        params![msg_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn set_message_usage(
+    conn: &Connection,
+    msg_id: &str,
+    prompt_tokens: u32,
+    completion_tokens: u32,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE messages SET prompt_tokens = ?1, completion_tokens = ?2 WHERE id = ?3",
+        params![prompt_tokens, completion_tokens, msg_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Make one message the active variant of its response group.
+pub fn activate_variant(conn: &Connection, msg_id: &str) -> Result<(), String> {
+    let group_id: Option<String> = conn
+        .query_row(
+            "SELECT response_group_id FROM messages WHERE id = ?1",
+            params![msg_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| format!("Message {} not found", msg_id))?;
+
+    let group_id = group_id.ok_or_else(|| "Message has no other variants".to_string())?;
+
     conn.execute(
-        "UPDATE conversations SET updated_at = ?1 WHERE id = ?2",
-        params![now, chat_id],
+        "UPDATE messages SET is_active = (id = ?1) WHERE response_group_id = ?2",
+        params![msg_id, group_id],
     )
     .map_err(|e| e.to_string())?;
+    Ok(())
+}
 
+/// Re-activate the newest variant of a group, used when a regeneration
+/// fails before its response was stored.
+pub fn reactivate_latest_variant(conn: &Connection, group_id: &str) -> Result<(), String> {
+    conn.execute(
+        r#"
+        UPDATE messages SET is_active = 1
+        WHERE id = (
+            SELECT id FROM messages
+            WHERE response_group_id = ?1
+            ORDER BY variant_index DESC
+            LIMIT 1
+        )
+        "#,
+        params![group_id],
+    )
+    .map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// Delete untouched "New chat" conversations that never got a message.
+/// Returns how many were removed.
+pub fn delete_empty_chats(conn: &mut Connection) -> Result<usize, String> {
+    let empty = r#"
+        SELECT c.id FROM conversations c
+        WHERE c.title = 'New chat'
+          AND NOT EXISTS (SELECT 1 FROM messages m WHERE m.conversation_id = c.id)
+    "#;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for table in ["drafts", "snapshots"] {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE conversation_id IN ({})", table, empty),
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    let removed = tx
+        .execute(&format!("DELETE FROM conversations WHERE id IN ({})", empty), [])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(removed)
+}
+
+/// Store a partial response as the chat's draft, replacing any previous one.
+pub fn save_draft(conn: &Connection, chat_id: &str, content: &str, thinking: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO drafts (conversation_id, content, thinking, updated_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![chat_id, content, thinking, unix_ms()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn load_draft(conn: &Connection, chat_id: &str) -> Result<Option<ChatDraft>, String> {
+    let draft = conn
+        .query_row(
+            "SELECT content, thinking, updated_at FROM drafts WHERE conversation_id = ?1",
+            params![chat_id],
+            |row| {
+                Ok(ChatDraft {
+                    chat_id: chat_id.to_string(),
+                    content: row.get(0)?,
+                    thinking: row.get(1)?,
+                    updated_at: row.get(2)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(draft)
+}
+
+pub fn delete_draft(conn: &Connection, chat_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM drafts WHERE conversation_id = ?1",
+        params![chat_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Every message row of a chat, including inactive variants, oldest first.
+pub fn load_snapshot_messages(conn: &Connection, chat_id: &str) -> Result<Vec<SnapshotMessage>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, role, content, thinking, images, created_at, duration_ms,
+                   response_group_id, variant_index, is_active, prompt_tokens, completion_tokens,
+                   truncated
+            FROM messages
+            WHERE conversation_id = ?1
+            ORDER BY created_at ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![chat_id], |row| {
+            Ok(SnapshotMessage {
+                id: row.get(0)?,
+                role: row.get(1)?,
+                content: row.get(2)?,
+                thinking: row.get(3)?,
+                images: row.get(4)?,
+                created_at: row.get(5)?,
+                duration_ms: row.get(6)?,
+                response_group_id: row.get(7)?,
+                variant_index: row.get(8)?,
+                is_active: row.get::<_, i64>(9)? != 0,
+                prompt_tokens: row.get(10)?,
+                completion_tokens: row.get(11)?,
+                truncated: row.get::<_, i64>(12)? != 0,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows {
+        out.push(r.map_err(|e| e.to_string())?);
+    }
+    Ok(out)
+}
+
+/// Replace all messages of a chat with the given rows, atomically.
+pub fn replace_chat_messages(
+    conn: &mut Connection,
+    chat_id: &str,
+    messages: &[SnapshotMessage],
+) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "DELETE FROM messages WHERE conversation_id = ?1",
+        params![chat_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    insert_message_rows(&tx, chat_id, messages)?;
+
+    tx.execute(
+        "UPDATE conversations SET updated_at = ?1, summary = '', summary_message_count = 0 WHERE id = ?2",
+        params![unix_ms(), chat_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Insert stored message rows into a chat, keeping their ids and metadata.
+fn insert_message_rows(conn: &Connection, chat_id: &str, messages: &[SnapshotMessage]) -> Result<(), String> {
+    for m in messages {
+        conn.execute(
+            r#"
+            INSERT INTO messages (id, conversation_id, role, content, thinking, images, created_at,
+                                  duration_ms, response_group_id, variant_index, is_active,
+                                  prompt_tokens, completion_tokens, truncated)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+            "#,
+            params![
+                m.id,
+                chat_id,
+                m.role,
+                m.content,
+                m.thinking,
+                m.images,
+                m.created_at,
+                m.duration_ms,
+                m.response_group_id,
+                m.variant_index,
+                m.is_active as i64,
+                m.prompt_tokens,
+                m.completion_tokens,
+                m.truncated as i64,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Copy a chat into a new conversation titled "<title> (copy)", up to and
+/// including `up_to_message_id` (all messages when `None`). Messages and
+/// response groups get fresh ids; branching from a regenerated variant makes
+/// it the active one in the copy. Returns the new chat id.
+pub fn duplicate_chat(conn: &mut Connection, chat_id: &str, up_to_message_id: Option<&str>) -> Result<String, String> {
+    let (title, system_prompt, model_id): (String, Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT title, system_prompt, model_id FROM conversations WHERE id = ?1",
+            params![chat_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|_| format!("Chat {} not found", chat_id))?;
+
+    let mut messages = load_snapshot_messages(conn, chat_id)?;
+    if let Some(cutoff) = up_to_message_id {
+        let pos = messages
+            .iter()
+            .position(|m| m.id == cutoff)
+            .ok_or_else(|| format!("Message {} not found in chat {}", cutoff, chat_id))?;
+        messages.truncate(pos + 1);
+
+        if let Some(group) = messages[pos].response_group_id.clone() {
+            for m in messages.iter_mut() {
+                if m.response_group_id.as_deref() == Some(group.as_str()) {
+                    m.is_active = m.id == cutoff;
+                }
+            }
+        }
+    }
+
+    let mut group_ids: HashMap<String, String> = HashMap::new();
+    for m in messages.iter_mut() {
+        m.id = uuid::Uuid::new_v4().to_string();
+        if let Some(group) = m.response_group_id.take() {
+            let fresh = group_ids
+                .entry(group)
+                .or_insert_with(|| uuid::Uuid::new_v4().to_string())
+                .clone();
+            m.response_group_id = Some(fresh);
+        }
+    }
+
+    let new_id = uuid::Uuid::new_v4().to_string();
+    let now = unix_ms();
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO conversations (id, title, summary, system_prompt, model_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![new_id, format!("{} (copy)", title), "", system_prompt, model_id, now, now],
+    )
+    .map_err(|e| e.to_string())?;
+    insert_message_rows(&tx, &new_id, &messages)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(new_id)
+}
+
+/// Replace a user message's text and drop everything after it in the chat
+/// (replies and their variants), atomically. Returns the chat id.
+pub fn edit_user_message(conn: &mut Connection, msg_id: &str, content: &str) -> Result<String, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let (chat_id, role, created_at): (String, String, i64) = tx
+        .query_row(
+            "SELECT conversation_id, role, created_at FROM messages WHERE id = ?1",
+            params![msg_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|_| format!("Message {} not found", msg_id))?;
+    if role != "user" {
+        return Err("Only user messages can be edited".to_string());
+    }
+
+    tx.execute(
+        "UPDATE messages SET content = ?1 WHERE id = ?2",
+        params![content, msg_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "DELETE FROM messages WHERE conversation_id = ?1 AND created_at > ?2 AND id != ?3",
+        params![chat_id, created_at, msg_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // A draft of the old reply no longer belongs to this prompt
+    tx.execute(
+        "DELETE FROM drafts WHERE conversation_id = ?1",
+        params![chat_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // The summary may describe the old text; rebuild it on the next request
+    tx.execute(
+        "UPDATE conversations SET updated_at = ?1, summary = '', summary_message_count = 0 WHERE id = ?2",
+        params![unix_ms(), chat_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(chat_id)
+}
+
 pub fn resolve_db_path(app: &AppHandle) -> Result<PathBuf, String> {
     let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
     Ok(dir.join("eigenAgent.sqlite3"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deleting_a_conversation_cascades_to_its_messages() {
+        let path = std::env::temp_dir().join(format!("eigen-test-{}.db", uuid::Uuid::new_v4()));
+        let conn = open_db(&path).unwrap();
+        init_db(&conn).unwrap();
+
+        insert_message(&conn, "chat", "user", "hello", "", &[], None).unwrap();
+        insert_message(&conn, "chat", "assistant", "hi", "", &[], Some(5)).unwrap();
+        conn.execute("DELETE FROM conversations WHERE id = 'chat'", []).unwrap();
+
+        let remaining: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM messages WHERE conversation_id = 'chat'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(remaining, 0);
+
+        drop(conn);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
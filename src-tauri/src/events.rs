@@ -0,0 +1,179 @@
+// src-tauri/src/events.rs
+//
+// Names of every event emitted to the frontend. Always emit through these
+// constants so a renamed event can't silently drift from its listeners, and
+// keep `EVENT_SCHEMA` in sync when adding one.
+
+use serde::Serialize;
+
+pub const CHAT_BEGIN: &str = "chat:begin";
+pub const CHAT_DELTA: &str = "chat:delta";
+pub const CHAT_END: &str = "chat:end";
+pub const CHAT_ERROR: &str = "chat:error";
+pub const CHATS_CHANGED: &str = "chats:changed";
+
+pub const MODEL_LOADING: &str = "model:loading";
+pub const MODEL_READY: &str = "model:ready";
+pub const MODEL_ERROR: &str = "model:error";
+pub const MODEL_OOM: &str = "model:oom";
+pub const MODEL_BACKEND: &str = "model:backend";
+pub const MODEL_NO_MODEL: &str = "model:no_model";
+pub const MODEL_STOPPED: &str = "model:stopped";
+pub const MODEL_CRASHED: &str = "model:crashed";
+pub const MODEL_SWITCHING: &str = "model:switching";
+pub const MODEL_MISMATCH: &str = "model:mismatch";
+pub const MODELS_CHANGED: &str = "models:changed";
+pub const COMPARE_PROGRESS: &str = "compare:progress";
+
+pub const DOWNLOAD_PROGRESS: &str = "download:progress";
+pub const DOWNLOAD_PAUSED: &str = "download:paused";
+pub const DOWNLOAD_RESUMED: &str = "download:resumed";
+pub const DOWNLOAD_RETRY: &str = "download:retry";
+pub const DOWNLOAD_COMPLETE: &str = "download:complete";
+pub const DOWNLOAD_ERROR: &str = "download:error";
+pub const VERIFY_PROGRESS: &str = "verify:progress";
+
+pub const SERVER_LOG_LINE: &str = "server:log_line";
+
+pub const SETTINGS_CHANGED: &str = "settings:changed";
+
+/// One emitted event: its name, the Rust payload type and when it fires.
+#[derive(Clone, Serialize)]
+pub struct EventInfo {
+    pub name: &'static str,
+    pub payload: &'static str,
+    pub description: &'static str,
+}
+
+pub const EVENT_SCHEMA: &[EventInfo] = &[
+    EventInfo {
+        name: CHAT_BEGIN,
+        payload: "ChatBeginPayload",
+        description: "An assistant reply started streaming",
+    },
+    EventInfo {
+        name: CHAT_DELTA,
+        payload: "ChatDeltaPayload",
+        description: "A chunk of answer and/or reasoning text",
+    },
+    EventInfo {
+        name: CHAT_END,
+        payload: "ChatEndPayload",
+        description: "The reply finished, was cancelled or was kept as a draft",
+    },
+    EventInfo {
+        name: CHAT_ERROR,
+        payload: "ChatErrorPayload",
+        description: "The stream failed mid-reply; the partial reply is kept as truncated",
+    },
+    EventInfo {
+        name: CHATS_CHANGED,
+        payload: "()",
+        description: "The chat list or a chat's messages changed",
+    },
+    EventInfo {
+        name: MODEL_LOADING,
+        payload: "()",
+        description: "llama-server is starting at app launch",
+    },
+    EventInfo {
+        name: MODEL_READY,
+        payload: "()",
+        description: "llama-server is healthy and accepting requests",
+    },
+    EventInfo {
+        name: MODEL_ERROR,
+        payload: "String",
+        description: "llama-server failed to start or stopped responding",
+    },
+    EventInfo {
+        name: MODEL_OOM,
+        payload: "ModelOomPayload",
+        description: "llama-server failed after reporting an out-of-memory error",
+    },
+    EventInfo {
+        name: MODEL_BACKEND,
+        payload: "AccelerationInfo",
+        description: "Compute backend (GPU or CPU) detected while llama-server starts",
+    },
+    EventInfo {
+        name: MODEL_NO_MODEL,
+        payload: "()",
+        description: "No model is installed, so the server was not started",
+    },
+    EventInfo {
+        name: MODEL_STOPPED,
+        payload: "()",
+        description: "llama-server was stopped with stop_server to free memory",
+    },
+    EventInfo {
+        name: MODEL_CRASHED,
+        payload: "ModelCrashedPayload",
+        description: "llama-server exited unexpectedly; an automatic restart may follow",
+    },
+    EventInfo {
+        name: MODEL_SWITCHING,
+        payload: "ModelSwitchPayload",
+        description: "Progress of a model switch or reload",
+    },
+    EventInfo {
+        name: MODEL_MISMATCH,
+        payload: "ModelMismatchPayload",
+        description: "A message was sent to a chat started with another model",
+    },
+    EventInfo {
+        name: MODELS_CHANGED,
+        payload: "()",
+        description: "The models directory or catalog changed",
+    },
+    EventInfo {
+        name: COMPARE_PROGRESS,
+        payload: "CompareProgressPayload",
+        description: "One model of a compare_models run changed stage",
+    },
+    EventInfo {
+        name: DOWNLOAD_PROGRESS,
+        payload: "DownloadProgressPayload",
+        description: "Bytes downloaded so far for a model",
+    },
+    EventInfo {
+        name: DOWNLOAD_PAUSED,
+        payload: "String",
+        description: "A download was paused and its .part files kept (model id)",
+    },
+    EventInfo {
+        name: DOWNLOAD_RESUMED,
+        payload: "DownloadResumedPayload",
+        description: "A file download continued from a .part file",
+    },
+    EventInfo {
+        name: DOWNLOAD_RETRY,
+        payload: "DownloadRetryPayload",
+        description: "A file download failed transiently and is about to retry",
+    },
+    EventInfo {
+        name: DOWNLOAD_COMPLETE,
+        payload: "DownloadCompletePayload",
+        description: "All files of a model finished downloading",
+    },
+    EventInfo {
+        name: DOWNLOAD_ERROR,
+        payload: "DownloadErrorPayload",
+        description: "A download failed; its .part files are kept for a retry",
+    },
+    EventInfo {
+        name: VERIFY_PROGRESS,
+        payload: "VerifyProgressPayload",
+        description: "One model was checked by verify_all_models",
+    },
+    EventInfo {
+        name: SERVER_LOG_LINE,
+        payload: "ServerLogLine",
+        description: "A line of llama-server stdout/stderr",
+    },
+    EventInfo {
+        name: SETTINGS_CHANGED,
+        payload: "AppSettings",
+        description: "Settings were saved or reset (the new settings)",
+    },
+];
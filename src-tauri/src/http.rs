@@ -0,0 +1,129 @@
+// src-tauri/src/http.rs
+
+use std::time::Duration;
+
+use reqwest::{Client, NoProxy, Proxy, StatusCode, Url};
+
+use crate::settings::NetworkSettings;
+use crate::state::LlamaServerManager;
+
+/// llama-server always runs on this machine, so it must never be proxied.
+const LOCAL_NO_PROXY: &str = "localhost,127.0.0.1,::1";
+const USER_AGENT: &str = concat!("eigenAgent/", env!("CARGO_PKG_VERSION"));
+/// Only connecting is bounded; streamed replies and downloads can take
+/// arbitrarily long once connected.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Build a reqwest client honoring the proxy settings.
+/// When no proxy is configured, reqwest falls back to the
+/// HTTP_PROXY / HTTPS_PROXY / NO_PROXY environment variables.
+pub fn build_http_client(network: &NetworkSettings) -> Result<Client, String> {
+    let mut builder = Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT);
+
+    let no_proxy = match non_empty(&network.no_proxy) {
+        Some(extra) => format!("{},{}", LOCAL_NO_PROXY, extra),
+        None => LOCAL_NO_PROXY.to_string(),
+    };
+
+    if let Some(url) = non_empty(&network.http_proxy) {
+        validate_proxy_url(url)?;
+        let proxy = Proxy::http(url)
+            .map_err(|e| format!("Invalid HTTP proxy: {}", e))?
+            .no_proxy(NoProxy::from_string(&no_proxy));
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(url) = non_empty(&network.https_proxy) {
+        validate_proxy_url(url)?;
+        let proxy = Proxy::https(url)
+            .map_err(|e| format!("Invalid HTTPS proxy: {}", e))?
+            .no_proxy(NoProxy::from_string(&no_proxy));
+        builder = builder.proxy(proxy);
+    }
+
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// The shared client. Cloning is cheap and keeps its connection pool, so
+/// every request reuses connections to llama-server and download hosts.
+pub fn http_client(state: &LlamaServerManager) -> Result<Client, String> {
+    let client = state.http_client.lock().map_err(|e| e.to_string())?;
+    Ok(client.clone())
+}
+
+/// Message for a failed request that names the proxy when it is the likely
+/// culprit (connecting or waiting failed while one was in use).
+pub fn describe_request_error(state: &LlamaServerManager, err: &reqwest::Error) -> String {
+    match active_proxy(state, err.url()) {
+        Some(proxy) if err.is_connect() || err.is_timeout() => format!(
+            "Request through proxy {} failed, check the proxy settings: {}",
+            proxy, err
+        ),
+        _ => err.to_string(),
+    }
+}
+
+/// Message for an unsuccessful response; a 407 comes from the proxy, not
+/// the server that was asked.
+pub fn describe_http_status(status: StatusCode) -> String {
+    if status == StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+        "The proxy requires authentication (HTTP 407); add credentials to the proxy URL".to_string()
+    } else {
+        format!("HTTP error: {}", status)
+    }
+}
+
+pub fn validate_proxy_url(url: &str) -> Result<(), String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid proxy URL {:?}: {}", url, e))?;
+
+    match parsed.scheme() {
+        "http" | "https" => {}
+        other => {
+            return Err(format!(
+                "Unsupported proxy scheme {:?} in {:?} (expected http or https)",
+                other, url
+            ))
+        }
+    }
+
+    if parsed.host_str().map_or(true, |h| h.is_empty()) {
+        return Err(format!("Proxy URL {:?} has no host", url));
+    }
+
+    Ok(())
+}
+
+/// The proxy a request to `url` goes through: the settings first, then the
+/// environment variables reqwest falls back to. Credentials are removed.
+fn active_proxy(state: &LlamaServerManager, url: Option<&Url>) -> Option<String> {
+    let network = state.app_settings.lock().ok()?.network.clone();
+    let https = url.map_or(true, |u| u.scheme() == "https");
+    let (configured, env_vars) = if https {
+        (&network.https_proxy, ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"])
+    } else {
+        (&network.http_proxy, ["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"])
+    };
+
+    let proxy = match non_empty(configured) {
+        Some(proxy) => proxy.to_string(),
+        None => env_vars
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.trim().is_empty())?,
+    };
+
+    match Url::parse(proxy.trim()) {
+        Ok(mut parsed) => {
+            let _ = parsed.set_username("");
+            let _ = parsed.set_password(None);
+            Some(parsed.to_string())
+        }
+        Err(_) => Some(proxy),
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
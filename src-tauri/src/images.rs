@@ -0,0 +1,107 @@
+// src-tauri/src/images.rs
+//
+// Checks on base64 image attachments and the data URLs they are sent as.
+
+use std::io::Cursor;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, ImageReader};
+
+/// Base64 characters decoded to sniff a stored image's format (12 bytes).
+const SNIFF_CHARS: usize = 16;
+/// Quality downscaled images are re-encoded at
+const JPEG_QUALITY: u8 = 85;
+
+/// MIME type of an image from its magic bytes, for the formats vision models
+/// accept.
+pub fn sniff_image_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Check that every attachment is valid base64 of a JPEG, PNG, GIF or WebP
+/// image, so a bad one is reported before anything is stored or sent.
+pub fn validate_images(images: &[String]) -> Result<(), String> {
+    for (i, img_base64) in images.iter().enumerate() {
+        let label = if images.len() > 1 {
+            format!("Image {}", i + 1)
+        } else {
+            "The image".to_string()
+        };
+        let bytes = STANDARD
+            .decode(img_base64)
+            .map_err(|_| format!("{} is not valid base64", label))?;
+        if sniff_image_mime(&bytes).is_none() {
+            return Err(format!("{} is not a JPEG, PNG, GIF or WebP image", label));
+        }
+    }
+    Ok(())
+}
+
+/// Validate attachments and downscale the ones whose longer side exceeds
+/// `max_dimension` (0 keeps every image as-is). Decoding is CPU-bound, so
+/// call this off the async runtime.
+pub fn prepare_images(images: Vec<String>, max_dimension: u32) -> Result<Vec<String>, String> {
+    validate_images(&images)?;
+    if max_dimension == 0 {
+        return Ok(images);
+    }
+    images
+        .into_iter()
+        .map(|img_base64| downscale_image(img_base64, max_dimension))
+        .collect()
+}
+
+/// Downscale an image to fit `max_dimension`, preserving its aspect ratio,
+/// and re-encode it as JPEG. Images that already fit are returned unchanged
+/// without decoding their pixels.
+fn downscale_image(img_base64: String, max_dimension: u32) -> Result<String, String> {
+    let bytes = STANDARD.decode(&img_base64).map_err(|e| e.to_string())?;
+    let (width, height) = ImageReader::new(Cursor::new(&bytes))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .into_dimensions()
+        .map_err(|e| format!("Failed to read image: {}", e))?;
+    if width.max(height) <= max_dimension {
+        return Ok(img_base64);
+    }
+
+    let img = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))?;
+    let resized = img.resize(max_dimension, max_dimension, FilterType::Triangle);
+
+    let mut out = Vec::new();
+    resized
+        .to_rgb8()
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY))
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    println!(
+        "[images] Downscaled {}x{} to {}x{} ({} -> {} bytes)",
+        width,
+        height,
+        resized.width(),
+        resized.height(),
+        bytes.len(),
+        out.len()
+    );
+    Ok(STANDARD.encode(out))
+}
+
+/// `data:` URL for an attachment, labelled with its actual format. Images
+/// stored before attachments were validated fall back to JPEG.
+pub fn image_data_url(img_base64: &str) -> String {
+    let mime = img_base64
+        .get(..SNIFF_CHARS)
+        .and_then(|head| STANDARD.decode(head).ok())
+        .and_then(|bytes| sniff_image_mime(&bytes))
+        .unwrap_or("image/jpeg");
+    format!("data:{};base64,{}", mime, img_base64)
+}
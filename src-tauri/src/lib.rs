@@ -1,73 +1,173 @@
 // src-tauri/src/lib.rs
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64},
     Mutex,
 };
 use std::time::{Duration, Instant};
 
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use tauri::{Emitter, Manager};
-use tauri_plugin_shell::ShellExt;
 
 mod commands;
 mod db;
+mod events;
+mod http;
+mod images;
 mod models;
+mod recording;
+mod response_cache;
+mod search;
 mod server;
 mod settings;
 mod state;
+mod summarizer;
+mod system;
+mod think;
 mod types;
 
 use commands::{
-    cancel_download, cancel_generation, chat_stream, delete_chat, delete_model,
-    download_model, generate_chat_title, get_chat_messages, get_current_model, list_chats,
-    list_models, model_status, new_chat, rename_chat, switch_model,
+    accept_model_license, add_tag, archive_chat, cancel_download, cancel_generation, cancel_switch,
+    cancel_to_draft, chat_once, chat_stream, check_catalog_conflicts, cleanup_empty_chats,
+    clear_current_model, clear_draft, compare_models, delete_chat, delete_model, delete_snapshot,
+    download_model, duplicate_chat, edit_message, estimate_download, export_database,
+    export_model_state, flush_db, generate_chat_title, get_acceleration_info, get_chat_messages,
+    get_chat_system_prompt, get_current_model, get_draft, get_model_details, get_server_log,
+    get_status, get_system_info, import_database, import_model, list_archived_chats, list_chats,
+    list_downloads, list_event_schema, list_models, list_models_by_capability, list_snapshots,
+    list_tags, migrate_legacy_model, model_status, new_chat, pause_download, pin_chat,
+    refresh_catalog, regenerate_response, reload_current_model, remove_tag, rename_chat,
+    rename_chat_final, restore_snapshot, resume_download, search_messages, select_variant,
+    set_chat_system_prompt, snapshot_chat, start_server, stop_server, switch_model, unarchive_chat,
+    unpin_chat, vacuum_database, verify_all_models, warm_prompt,
 };
-use db::{init_db, open_db, resolve_db_path};
-use models::{find_model_files, get_model_paths, get_models_dir, load_or_create_catalog, scan_models_dir};
-use server::wait_for_server_ready;
-use settings::{get_default_settings, load_settings, save_settings, AppSettings};
-use state::{LlamaServerManager, SERVER_PORT};
+use commands::model::restart_server_with;
+use db::{delete_empty_chats, init_db, open_db, resolve_db_path};
+use events::{MODELS_CHANGED, MODEL_ERROR, MODEL_LOADING, MODEL_NO_MODEL, SETTINGS_CHANGED};
+use http::build_http_client;
+use models::{
+    find_model_files, get_model_paths, get_models_dir, load_or_create_catalog,
+    loaded_model_capabilities, scan_models_dir,
+};
+use server::{await_server_startup, spawn_health_poller, spawn_llama_server};
+use settings::{
+    get_default_settings, load_settings, save_settings, validate_settings, AppSettings,
+    NetworkSettings,
+};
+use state::LlamaServerManager;
 
 // ==================== Settings Commands ====================
 
 #[tauri::command]
-fn cmd_load_settings(state: tauri::State<'_, LlamaServerManager>) -> Result<AppSettings, String> {
+fn get_settings(state: tauri::State<'_, LlamaServerManager>) -> Result<AppSettings, String> {
     let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
     Ok(settings.clone())
 }
 
 #[tauri::command]
-fn cmd_save_settings(
+fn update_settings(
     new_settings: AppSettings,
+    app: tauri::AppHandle,
     state: tauri::State<'_, LlamaServerManager>,
-) -> Result<(), String> {
-    // Save to disk
-    save_settings(&new_settings)?;
+) -> Result<AppSettings, String> {
+    validate_settings(&new_settings)?;
+    apply_settings(&app, &state, new_settings.clone())?;
+    println!("[settings] Settings updated");
+    Ok(new_settings)
+}
 
-    // Update in-memory state
-    let mut settings = state.app_settings.lock().map_err(|e| e.to_string())?;
-    *settings = new_settings;
+#[tauri::command]
+fn cmd_load_settings(state: tauri::State<'_, LlamaServerManager>) -> Result<AppSettings, String> {
+    get_settings(state)
+}
 
-    println!("[settings] Settings updated");
-    Ok(())
+#[tauri::command]
+fn cmd_save_settings(
+    new_settings: AppSettings,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, LlamaServerManager>,
+) -> Result<(), String> {
+    update_settings(new_settings, app, state).map(|_| ())
 }
 
 #[tauri::command]
-fn cmd_reset_settings(state: tauri::State<'_, LlamaServerManager>) -> Result<AppSettings, String> {
+fn cmd_reset_settings(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, LlamaServerManager>,
+) -> Result<AppSettings, String> {
     let default_settings = get_default_settings();
+    apply_settings(&app, &state, default_settings.clone())?;
+    println!("[settings] Settings reset to defaults");
+    Ok(default_settings)
+}
 
-    // Save defaults to disk
-    save_settings(&default_settings)?;
+/// Persist `new_settings`, swap them into state and tell the frontend.
+fn apply_settings(
+    app: &tauri::AppHandle,
+    state: &LlamaServerManager,
+    new_settings: AppSettings,
+) -> Result<(), String> {
+    // Build the client first so bad proxy settings are rejected before saving
+    let network_changed =
+        state.app_settings.lock().map_err(|e| e.to_string())?.network != new_settings.network;
+    let client = if network_changed {
+        Some(build_http_client(&new_settings.network)?)
+    } else {
+        None
+    };
+
+    // Save to disk
+    save_settings(&new_settings)?;
+    if let Some(client) = client {
+        *state.http_client.lock().map_err(|e| e.to_string())? = client;
+    }
 
     // Update in-memory state
-    let mut settings = state.app_settings.lock().map_err(|e| e.to_string())?;
-    *settings = default_settings.clone();
+    let old_args = {
+        let mut settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        let old_args = (settings.behavior.server_args(), settings.behavior.server_port);
+        *settings = new_settings.clone();
+        old_args
+    };
+
+    let _ = app.emit(SETTINGS_CHANGED, &new_settings);
+    restart_if_server_args_changed(app, state, old_args)
+}
 
-    println!("[settings] Settings reset to defaults");
-    Ok(default_settings)
+/// Reload the current model in the background when a setting that ends up
+/// on the llama-server command line (e.g. `gpuLayers` or the port) changed.
+/// `old_args` are the previous `server_args()` and `serverPort`.
+fn restart_if_server_args_changed(
+    app: &tauri::AppHandle,
+    state: &LlamaServerManager,
+    old_args: (Vec<String>, u16),
+) -> Result<(), String> {
+    let new_args = {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        (settings.behavior.server_args(), settings.behavior.server_port)
+    };
+    if new_args == old_args {
+        return Ok(());
+    }
+
+    let model_id = match state.current_model_id.lock().map_err(|e| e.to_string())?.clone() {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+    let model_path = state.model_path.lock().map_err(|e| e.to_string())?.clone();
+    let mmproj_path = state.mmproj_path.lock().map_err(|e| e.to_string())?.clone();
+
+    println!("[settings] Server arguments changed, restarting with {}", model_id);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<LlamaServerManager>();
+        if let Err(e) = restart_server_with(&app, &state, model_id, model_path, mmproj_path).await {
+            eprintln!("[settings] Restart after settings change failed: {}", e);
+        }
+    });
+    Ok(())
 }
 
 // ==================== App Entry Point ====================
@@ -98,6 +198,13 @@ pub fn run() {
             });
             println!("[settings] Loaded settings (theme: {})", app_settings.appearance.theme);
 
+            if app_settings.behavior.cleanup_empty_chats_on_startup {
+                match open_db(&db_path).and_then(|mut conn| delete_empty_chats(&mut conn)) {
+                    Ok(removed) => println!("[db] Removed {} empty chats", removed),
+                    Err(e) => eprintln!("[db] Failed to clean up empty chats: {}", e),
+                }
+            }
+
             // Load or create model catalog
             let catalog = load_or_create_catalog(&app_handle)?;
             println!("[catalog] loaded {} models", catalog.models.len());
@@ -147,7 +254,11 @@ pub fn run() {
                 found
             };
 
-            let server_url = format!("http://127.0.0.1:{}", SERVER_PORT);
+            let server_port = app_settings.behavior.server_port;
+            let http_client = build_http_client(&app_settings.network).or_else(|e| {
+                eprintln!("[network] Ignoring invalid proxy settings: {}", e);
+                build_http_client(&NetworkSettings::default())
+            })?;
 
             // Store state - use empty path if no model found
             let (model_path, mmproj_path, current_model_id) = match found_model {
@@ -166,20 +277,37 @@ pub fn run() {
             };
 
             let has_model = current_model_id.is_some();
+            let current_capabilities = current_model_id
+                .as_deref()
+                .map(|id| loaded_model_capabilities(&catalog, id, mmproj_path.is_some()));
 
             app.manage(LlamaServerManager {
                 process: Mutex::new(None),
-                server_url: server_url.clone(),
+                server_started_at: Mutex::new(None),
+                server_port: AtomicU16::new(server_port),
                 is_ready: AtomicBool::new(false),
-                is_cancelled: AtomicBool::new(false),
+                generations: Mutex::new(HashMap::new()),
+                switch_cancelled: AtomicBool::new(false),
+                switch_in_progress: AtomicBool::new(false),
+                server_generation: AtomicU64::new(0),
+                crash_count: AtomicU32::new(0),
+                draft_requests: Mutex::new(HashSet::new()),
+                rename_generations: Mutex::new(HashMap::new()),
+                rename_counter: AtomicU64::new(0),
+                title_generations: Mutex::new(HashSet::new()),
                 db_path,
                 models_dir,
                 model_path: Mutex::new(model_path.clone()),
                 mmproj_path: Mutex::new(mmproj_path.clone()),
                 current_model_id: Mutex::new(current_model_id),
+                current_capabilities: Mutex::new(current_capabilities),
                 active_downloads: Mutex::new(HashMap::new()),
                 downloading_progress: Mutex::new(HashMap::new()),
                 app_settings: Mutex::new(app_settings),
+                server_log: Mutex::new(VecDeque::new()),
+                oom_line: Mutex::new(None),
+                acceleration: Mutex::new(None),
+                http_client: Mutex::new(http_client),
             });
 
             print!("[app] Do we have model: {}\n", has_model);
@@ -187,97 +315,29 @@ pub fn run() {
             // Only start the server if we have a model
             if has_model {
                 // Emit model loading
-                let _ = app_handle.emit("model:loading", ());
+                let _ = app_handle.emit(MODEL_LOADING, ());
 
                 // Spawn llama-server in background
-                let model_path_clone = model_path.clone();
-                let mmproj_path_clone = mmproj_path.clone();
-
                 tauri::async_runtime::spawn(async move {
                     let state = app_handle.state::<LlamaServerManager>();
-
-                    // Build sidecar command
-                    let shell = app_handle.shell();
-                    let mut cmd = shell
-                        .sidecar("llama-server")
-                        .expect("Failed to create sidecar command");
-
-                    // Get context length and max tokens from settings
-                    let (ctx_size, max_tokens) = {
-                        let settings = state.app_settings.lock().unwrap();
-                        (
-                            settings.behavior.context_length.to_string(),
-                            settings.behavior.max_tokens.to_string(),
-                        )
-                    };
-
-                    cmd = cmd
-                        .args(["-m", model_path_clone.to_str().unwrap()])
-                        .args(["--host", "127.0.0.1"])
-                        .args(["--port", &SERVER_PORT.to_string()])
-                        .args(["--ctx-size", &ctx_size])
-                        .args(["--n-predict", &max_tokens]);
-
-                    // Add vision projector if available
-                    if let Some(ref mmproj) = mmproj_path_clone {
-                        cmd = cmd.args(["--mmproj", mmproj.to_str().unwrap()]);
-                    }
-
-                    // Spawn the server
-                    match cmd.spawn() {
-                        Ok((mut rx, child)) => {
-                            // Store the child process
-                            if let Ok(mut guard) = state.process.lock() {
-                                *guard = Some(child);
-                            }
-
-                            // Log server output in background
-                            tauri::async_runtime::spawn(async move {
-                                while let Some(event) = rx.recv().await {
-                                    match event {
-                                        tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
-                                            println!(
-                                                "[llama-server] {}",
-                                                String::from_utf8_lossy(&line)
-                                            );
-                                        }
-                                        tauri_plugin_shell::process::CommandEvent::Stderr(line) => {
-                                            eprintln!(
-                                                "[llama-server] {}",
-                                                String::from_utf8_lossy(&line)
-                                            );
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                            });
-
-                            // Wait for server to be ready
-                            match wait_for_server_ready(&state.server_url, 120).await {
-                                Ok(()) => {
-                                    state.is_ready.store(true, Ordering::SeqCst);
-                                    let _ = app_handle.emit("model:ready", ());
-                                    println!("[llama-server] Ready!");
-                                }
-                                Err(e) => {
-                                    let _ = app_handle.emit("model:error", e);
-                                }
-                            }
+                    match spawn_llama_server(&app_handle, &state, &model_path, mmproj_path.as_deref()) {
+                        Ok(spawned_at) => {
+                            let _ = await_server_startup(&app_handle, spawned_at).await;
                         }
                         Err(e) => {
-                            let _ = app_handle.emit(
-                                "model:error",
-                                format!("Failed to spawn llama-server: {}", e),
-                            );
+                            let _ = app_handle.emit(MODEL_ERROR, e);
                         }
                     }
                 });
             } else {
                 // Emit no_model event so frontend knows to show warning
                 println!("[model] No model installed, emitting model:no_model event");
-                let _ = app_handle.emit("model:no_model", ());
+                let _ = app_handle.emit(MODEL_NO_MODEL, ());
             }
 
+            // Keep is_ready honest if the server hangs without exiting
+            spawn_health_poller(app.handle().clone());
+
             // Set up file watcher for models directory
             let models_dir_for_watcher = get_models_dir(&app.handle().clone())?;
             let app_handle_for_watcher = app.handle().clone();
@@ -323,7 +383,7 @@ pub fn run() {
                             // Debounce: only emit if at least 1 second since last emit
                             if last_emit.elapsed() > Duration::from_secs(1) {
                                 println!("[watcher] Models directory changed, emitting event");
-                                let _ = app_handle_for_watcher.emit("models:changed", ());
+                                let _ = app_handle_for_watcher.emit(MODELS_CHANGED, ());
                                 last_emit = Instant::now();
                             }
                         }
@@ -344,21 +404,77 @@ pub fn run() {
             model_status,
             new_chat,
             list_chats,
+            list_downloads,
+            list_event_schema,
             get_chat_messages,
             rename_chat,
+            rename_chat_final,
+            search_messages,
+            snapshot_chat,
+            list_snapshots,
+            restore_snapshot,
+            delete_snapshot,
             generate_chat_title,
             delete_chat,
+            flush_db,
+            export_database,
+            import_database,
+            vacuum_database,
             cancel_generation,
+            cleanup_empty_chats,
+            clear_current_model,
+            cancel_to_draft,
+            get_draft,
+            clear_draft,
             chat_stream,
+            regenerate_response,
+            select_variant,
+            edit_message,
+            warm_prompt,
             list_models,
+            list_models_by_capability,
             get_current_model,
+            get_model_details,
+            accept_model_license,
             switch_model,
+            reload_current_model,
             download_model,
+            pause_download,
+            resume_download,
+            estimate_download,
             cancel_download,
             delete_model,
+            migrate_legacy_model,
+            refresh_catalog,
+            chat_once,
+            get_chat_system_prompt,
+            set_chat_system_prompt,
+            list_archived_chats,
+            archive_chat,
+            unarchive_chat,
+            pin_chat,
+            unpin_chat,
+            add_tag,
+            remove_tag,
+            list_tags,
+            get_status,
+            stop_server,
+            start_server,
+            get_acceleration_info,
+            get_system_info,
+            duplicate_chat,
+            cancel_switch,
+            check_catalog_conflicts,
+            export_model_state,
+            compare_models,
+            import_model,
+            verify_all_models,
+            get_server_log,
             cmd_load_settings,
             cmd_save_settings,
-            cmd_reset_settings
+            cmd_reset_settings,
+            get_settings,
+            update_settings
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
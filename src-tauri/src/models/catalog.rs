@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 use tauri::AppHandle;
 use tauri::Manager;
 
-use crate::types::{ModelCatalog, ModelCatalogEntry};
+use crate::types::{CatalogConflict, ModelCapabilities, ModelCatalog, ModelCatalogEntry, ModelFile};
 
 pub fn get_catalog_path(app: &AppHandle) -> Result<PathBuf, String> {
     use crate::models::discovery::get_models_dir;
@@ -17,8 +17,7 @@ pub fn load_or_create_catalog(app: &AppHandle) -> Result<ModelCatalog, String> {
 
     if catalog_path.exists() {
         let content = std::fs::read_to_string(&catalog_path).map_err(|e| e.to_string())?;
-        let catalog: ModelCatalog = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-        return Ok(catalog);
+        return parse_catalog(&content);
     }
 
     // Try to load from bundled resources
@@ -33,7 +32,7 @@ pub fn load_or_create_catalog(app: &AppHandle) -> Result<ModelCatalog, String> {
             let content = std::fs::read_to_string(bundled_path).map_err(|e| e.to_string())?;
             // Copy to user directory
             std::fs::write(&catalog_path, &content).map_err(|e| e.to_string())?;
-            let catalog: ModelCatalog = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+            let catalog = parse_catalog(&content)?;
             println!("[catalog] Copied bundled catalog to {}", catalog_path.display());
             return Ok(catalog);
         }
@@ -50,47 +49,279 @@ pub fn load_or_create_catalog(app: &AppHandle) -> Result<ModelCatalog, String> {
     Ok(default_catalog)
 }
 
+/// Parse catalog JSON one entry at a time, so a single malformed entry is
+/// logged and skipped instead of discarding the whole catalog. Only fails
+/// when the file isn't JSON or has no `models` array.
+pub fn parse_catalog(content: &str) -> Result<ModelCatalog, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Invalid catalog JSON: {}", e))?;
+
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+    let entries = value
+        .get("models")
+        .and_then(|m| m.as_array())
+        .ok_or_else(|| "Catalog has no \"models\" array".to_string())?;
+
+    let mut models = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.iter().enumerate() {
+        match serde_json::from_value::<ModelCatalogEntry>(entry.clone()) {
+            Ok(model) => models.push(model),
+            Err(e) => {
+                let id = entry.get("id").and_then(|id| id.as_str()).unwrap_or("<no id>");
+                eprintln!("[catalog] Skipping entry {} ({}): {}", index, id, e);
+            }
+        }
+    }
+
+    Ok(ModelCatalog { version, models })
+}
+
+/// Write the catalog back to the user's catalog file.
+pub fn save_catalog(app: &AppHandle, catalog: &ModelCatalog) -> Result<(), String> {
+    let catalog_path = get_catalog_path(app)?;
+    let content = serde_json::to_string_pretty(catalog).map_err(|e| e.to_string())?;
+    std::fs::write(&catalog_path, content).map_err(|e| e.to_string())?;
+    println!("[catalog] Saved {} models to {}", catalog.models.len(), catalog_path.display());
+    Ok(())
+}
+
+/// Entries added on this machine (imported or migrated models) have no
+/// download URL and only exist in the local catalog.
+pub fn is_local_entry(entry: &ModelCatalogEntry) -> bool {
+    entry.files.model.url.is_empty()
+}
+
+/// Capabilities of `model_id` as loaded: its catalog entry's, or for a model
+/// outside the catalog (the legacy model) vision only when a projector is
+/// loaded with it.
+pub fn loaded_model_capabilities(
+    catalog: &ModelCatalog,
+    model_id: &str,
+    has_mmproj: bool,
+) -> ModelCapabilities {
+    match catalog.models.iter().find(|e| e.id == model_id) {
+        Some(entry) => entry.capabilities.clone(),
+        None => ModelCapabilities {
+            vision: has_mmproj,
+            thinking: false,
+            tools: false,
+        },
+    }
+}
+
+/// `incoming` with the local entries of `current` it doesn't replace
+/// appended, so refreshing from a remote catalog keeps imported models.
+pub fn merge_remote_catalog(current: &ModelCatalog, incoming: ModelCatalog) -> ModelCatalog {
+    let mut merged = incoming;
+    let local: Vec<ModelCatalogEntry> = current
+        .models
+        .iter()
+        .filter(|e| is_local_entry(e) && !merged.models.iter().any(|m| m.id == e.id))
+        .cloned()
+        .collect();
+    merged.models.extend(local);
+    merged
+}
+
+/// Turn a display name into a catalog id (`"Qwen 2.5 7B"` -> `"qwen-2.5-7b"`).
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() || c == '.' {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches(|c| c == '-' || c == '.').to_string()
+}
+
+/// Find an id based on `base` that is neither in the catalog nor used by
+/// an existing directory under `models_dir`.
+pub fn unique_model_id(models_dir: &Path, catalog: &ModelCatalog, base: &str) -> String {
+    let base = if base.is_empty() { "model" } else { base };
+    let taken = |id: &str| {
+        catalog.models.iter().any(|e| e.id == id) || get_model_dir(models_dir, id).exists()
+    };
+
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|id| !taken(id))
+        .unwrap()
+}
+
+/// Problems applying `incoming` over `current` would cause: ids that appear
+/// twice, and ids whose files on disk belong to a different model than the
+/// one `incoming` describes (which would then be served under its name).
+pub fn find_catalog_conflicts(
+    models_dir: &Path,
+    current: &ModelCatalog,
+    incoming: &ModelCatalog,
+) -> Vec<CatalogConflict> {
+    let mut conflicts = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for entry in &incoming.models {
+        if !seen.insert(entry.id.as_str()) {
+            conflicts.push(CatalogConflict {
+                model_id: entry.id.clone(),
+                kind: "duplicate_id".to_string(),
+                detail: format!("{} appears more than once", entry.id),
+            });
+            continue;
+        }
+
+        if !get_model_dir(models_dir, &entry.id).exists() {
+            continue;
+        }
+
+        let existing = current.models.iter().find(|e| e.id == entry.id);
+        let detail = match existing {
+            None => Some(format!(
+                "{} already has files on disk that are not from the catalog",
+                entry.id
+            )),
+            Some(existing) => describe_file_mismatch(existing, entry),
+        };
+        if let Some(detail) = detail {
+            conflicts.push(CatalogConflict {
+                model_id: entry.id.clone(),
+                kind: "file_mismatch".to_string(),
+                detail,
+            });
+        }
+    }
+
+    conflicts
+}
+
+fn describe_file_mismatch(existing: &ModelCatalogEntry, incoming: &ModelCatalogEntry) -> Option<String> {
+    let old_files = existing.files.all();
+    let new_files = incoming.files.all();
+
+    let mut old_names: Vec<&str> = old_files.iter().map(|f| f.filename.as_str()).collect();
+    let mut new_names: Vec<&str> = new_files.iter().map(|f| f.filename.as_str()).collect();
+    old_names.sort();
+    new_names.sort();
+    if old_names != new_names {
+        return Some(format!(
+            "{} changes files from [{}] to [{}]",
+            incoming.id,
+            old_names.join(", "),
+            new_names.join(", ")
+        ));
+    }
+
+    new_files.iter().find_map(|new| {
+        let old = old_files.iter().find(|f| f.filename == new.filename)?;
+        match (&old.sha256, &new.sha256) {
+            (Some(a), Some(b)) if !a.eq_ignore_ascii_case(b) => {
+                Some(format!("{} has a different checksum for {}", incoming.id, new.filename))
+            }
+            _ => None,
+        }
+    })
+}
+
 pub fn get_model_dir(models_dir: &Path, model_id: &str) -> PathBuf {
     models_dir.join(model_id)
 }
 
 pub fn is_model_downloaded(models_dir: &Path, entry: &ModelCatalogEntry) -> bool {
     let model_dir = get_model_dir(models_dir, &entry.id);
-    let model_path = model_dir.join(&entry.files.model.filename);
 
-    if !model_path.exists() {
-        return false;
-    }
+    // Main model, every shard and the mmproj (if required) must all exist,
+    // and no file may still be mid-download
+    entry
+        .files
+        .all()
+        .iter()
+        .all(|file| model_dir.join(&file.filename).exists())
+        && !has_partial_download(models_dir, entry)
+}
 
-    // Check mmproj if required
-    if let Some(ref mmproj) = entry.files.mmproj {
-        let mmproj_path = model_dir.join(&mmproj.filename);
-        if !mmproj_path.exists() {
-            return false;
-        }
-    }
+/// Where a file is written while it downloads; renamed once complete.
+pub fn partial_download_path(model_dir: &Path, filename: &str) -> PathBuf {
+    model_dir.join(format!("{}.part", filename))
+}
 
-    true
+/// Written when the user pauses a download, so leftover `.part` files from
+/// one that was interrupted (crash, quit, failure) aren't reported as paused.
+pub fn pause_marker_path(model_dir: &Path) -> PathBuf {
+    model_dir.join(".paused")
 }
 
-pub fn get_model_paths(models_dir: &Path, entry: &ModelCatalogEntry) -> Option<(PathBuf, Option<PathBuf>)> {
+/// Whether the user paused this model's download (rather than it being cut off).
+pub fn is_download_paused(models_dir: &Path, entry: &ModelCatalogEntry) -> bool {
+    pause_marker_path(&get_model_dir(models_dir, &entry.id)).exists()
+}
+
+/// Whether an interrupted download left `.part` files that can be resumed.
+pub fn has_partial_download(models_dir: &Path, entry: &ModelCatalogEntry) -> bool {
     let model_dir = get_model_dir(models_dir, &entry.id);
-    let model_path = model_dir.join(&entry.files.model.filename);
+    entry
+        .files
+        .all()
+        .iter()
+        .any(|file| partial_download_path(&model_dir, &file.filename).exists())
+}
 
-    if !model_path.exists() {
+pub fn get_model_paths(models_dir: &Path, entry: &ModelCatalogEntry) -> Option<(PathBuf, Option<PathBuf>)> {
+    if !is_model_downloaded(models_dir, entry) {
         return None;
     }
 
+    // Only the first shard is passed to llama-server; it loads the rest itself
+    let model_dir = get_model_dir(models_dir, &entry.id);
+    let model_path = model_dir.join(&entry.files.model.filename);
     let mmproj_path = entry.files.mmproj.as_ref().map(|mmproj| {
         model_dir.join(&mmproj.filename)
     });
 
-    // Check mmproj exists if required
-    if let Some(ref path) = mmproj_path {
-        if !path.exists() {
-            return None;
+    Some((model_path, mmproj_path))
+}
+
+/// Catalog sizes are frequently rounded, so a file is only reported as
+/// corrupt when it differs from the expected size by more than this fraction.
+const SIZE_TOLERANCE: f64 = 0.05;
+
+/// Check a downloaded file against its catalog description.
+/// Returns a human-readable problem when the file looks missing or corrupt.
+pub fn verify_model_file(model_dir: &Path, file: &ModelFile) -> Result<(), String> {
+    let path = model_dir.join(&file.filename);
+    let metadata =
+        std::fs::metadata(&path).map_err(|_| format!("{} is missing", file.filename))?;
+
+    let actual = metadata.len();
+    if actual == 0 {
+        return Err(format!("{} is empty", file.filename));
+    }
+
+    if file.size_bytes > 0 {
+        let expected = file.size_bytes as f64;
+        let diff = (actual as f64 - expected).abs() / expected;
+        if diff > SIZE_TOLERANCE {
+            return Err(format!(
+                "{} is {} bytes, expected about {}",
+                file.filename, actual, file.size_bytes
+            ));
         }
     }
 
-    Some((model_path, mmproj_path))
+    Ok(())
+}
+
+/// Check every file belonging to a catalog entry, collecting all problems.
+pub fn verify_model_files(models_dir: &Path, entry: &ModelCatalogEntry) -> Vec<String> {
+    let model_dir = get_model_dir(models_dir, &entry.id);
+
+    entry
+        .files
+        .all()
+        .into_iter()
+        .filter_map(|file| verify_model_file(&model_dir, file).err())
+        .collect()
 }
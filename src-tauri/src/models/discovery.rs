@@ -2,6 +2,7 @@
 
 use std::path::{Path, PathBuf};
 
+use regex::Regex;
 use tauri::AppHandle;
 use tauri::Manager;
 
@@ -34,7 +35,7 @@ pub fn scan_models_dir(models_dir: &Path) -> Option<(PathBuf, Option<PathBuf>)>
 
                     if filename.contains("mmproj") {
                         mmproj = Some(path);
-                    } else if main_model.is_none() {
+                    } else if main_model.is_none() && !is_secondary_shard(&filename) {
                         main_model = Some(path);
                     }
                 }
@@ -45,6 +46,37 @@ pub fn scan_models_dir(models_dir: &Path) -> Option<(PathBuf, Option<PathBuf>)>
     main_model.map(|m| (m, mmproj))
 }
 
+/// True for the 2nd..Nth file of a split GGUF (`name-00002-of-00003.gguf`).
+/// Only the first shard should be handed to llama-server.
+fn is_secondary_shard(filename: &str) -> bool {
+    let re = Regex::new(r"-(\d{5})-of-\d{5}\.gguf$").unwrap();
+    re.captures(filename)
+        .and_then(|c| c[1].parse::<u32>().ok())
+        .map_or(false, |index| index > 1)
+}
+
+/// For the first shard of a split GGUF, find the remaining shards next to it
+/// (sorted). Returns an empty list for single-file models.
+pub fn find_secondary_shards(first_shard: &Path) -> Vec<PathBuf> {
+    let filename = match first_shard.file_name() {
+        Some(f) => f.to_string_lossy().to_string(),
+        None => return Vec::new(),
+    };
+    let re = Regex::new(r"^(.*)-00001-of-(\d{5})\.gguf$").unwrap();
+    let caps = match re.captures(&filename) {
+        Some(c) => c,
+        None => return Vec::new(),
+    };
+    let prefix = caps[1].to_string();
+    let count: u32 = caps[2].parse().unwrap_or(1);
+    let dir = first_shard.parent().unwrap_or_else(|| Path::new("."));
+
+    (2..=count)
+        .map(|i| dir.join(format!("{}-{:05}-of-{}.gguf", prefix, i, &caps[2])))
+        .filter(|p| p.exists())
+        .collect()
+}
+
 pub fn find_model_files(app: &AppHandle) -> Result<(PathBuf, Option<PathBuf>), String> {
     // 1. Check app data directory first (production location)
     let app_data_models = app
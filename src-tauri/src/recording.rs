@@ -0,0 +1,109 @@
+// src-tauri/src/recording.rs
+//
+// Opt-in recording of chat requests and their streamed responses as JSONL,
+// plus a replay mode that serves recorded responses instead of calling
+// llama-server. Intended for UI testing and bug reproduction.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::db::unix_ms;
+use crate::types::OpenAIRequest;
+
+const REDACTED: &str = "[redacted]";
+
+/// One request and every SSE `data:` payload the server sent back.
+#[derive(Serialize, Deserialize)]
+pub struct RecordedExchange {
+    pub recorded_at: i64,
+    pub request: Value,
+    pub chunks: Vec<String>,
+}
+
+/// Append an exchange to the recording file, one JSON object per line.
+pub fn append_exchange(path: &Path, request: &OpenAIRequest, chunks: Vec<String>) -> Result<(), String> {
+    let exchange = RecordedExchange {
+        recorded_at: unix_ms(),
+        request: redacted_request(request)?,
+        chunks,
+    };
+    let line = serde_json::to_string(&exchange).map_err(|e| e.to_string())?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open recording file: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write recording: {}", e))?;
+    Ok(())
+}
+
+/// Find the recorded response for a request. An exchange whose messages
+/// match exactly wins; otherwise the latest one with the same final user
+/// message is used so replays survive small history differences.
+pub fn find_replay(path: &Path, request: &OpenAIRequest) -> Result<Vec<String>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read recording file: {}", e))?;
+
+    let wanted = redacted_request(request)?;
+    let wanted_messages = &wanted["messages"];
+    let wanted_last_user = last_user_message(wanted_messages);
+
+    let mut fallback: Option<Vec<String>> = None;
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let exchange: RecordedExchange = match serde_json::from_str(line) {
+            Ok(e) => e,
+            Err(e) => {
+                eprintln!("[replay] Skipping malformed line {}: {}", index + 1, e);
+                continue;
+            }
+        };
+
+        if &exchange.request["messages"] == wanted_messages {
+            return Ok(exchange.chunks);
+        }
+        if wanted_last_user.is_some() && last_user_message(&exchange.request["messages"]) == wanted_last_user {
+            fallback = Some(exchange.chunks);
+        }
+    }
+
+    fallback.ok_or_else(|| "No recorded response matches this conversation".to_string())
+}
+
+// ───────────────── private helpers ─────────────────
+
+/// Serialize a request with image data replaced, so recordings stay small
+/// and never contain user images.
+fn redacted_request(request: &OpenAIRequest) -> Result<Value, String> {
+    let mut value = serde_json::to_value(request).map_err(|e| e.to_string())?;
+
+    if let Some(messages) = value.get_mut("messages").and_then(Value::as_array_mut) {
+        for message in messages {
+            if let Some(parts) = message.get_mut("content").and_then(Value::as_array_mut) {
+                for part in parts {
+                    if let Some(url) = part.pointer_mut("/image_url/url") {
+                        *url = Value::String(REDACTED.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+fn last_user_message(messages: &Value) -> Option<&Value> {
+    messages
+        .as_array()?
+        .iter()
+        .rev()
+        .find(|m| m["role"] == "user")
+        .map(|m| &m["content"])
+}
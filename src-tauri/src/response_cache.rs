@@ -0,0 +1,110 @@
+// src-tauri/src/response_cache.rs
+//
+// Cache of deterministic (temperature 0) answers keyed by a hash of the
+// loaded model and the full request, so repeated identical queries can be
+// answered without running the model. Stochastic requests are never cached.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+use crate::db::unix_ms;
+use crate::types::OpenAIRequest;
+
+/// Stable hash of the normalized request: loaded model, messages and every
+/// sampling parameter. `stream` is excluded since it doesn't change the answer.
+pub fn response_cache_key(model_id: Option<&str>, request: &OpenAIRequest) -> Result<String, String> {
+    let mut value = serde_json::to_value(request).map_err(|e| e.to_string())?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.remove("stream");
+    }
+
+    // serde_json maps are sorted by key, so the serialization is canonical
+    let mut hasher = Sha256::new();
+    hasher.update(model_id.unwrap_or_default().as_bytes());
+    hasher.update([0u8]);
+    hasher.update(value.to_string().as_bytes());
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Only greedy sampling (temperature exactly 0) produces a repeatable answer.
+pub fn is_deterministic(request: &OpenAIRequest) -> bool {
+    request.temperature == Some(0.0)
+}
+
+pub fn lookup_cached_response(conn: &Connection, key: &str) -> Result<Option<(String, String)>, String> {
+    conn.query_row(
+        "SELECT content, thinking FROM response_cache WHERE hash = ?1",
+        params![key],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+pub fn store_cached_response(conn: &Connection, key: &str, content: &str, thinking: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO response_cache (hash, content, thinking, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![key, content, thinking, unix_ms()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+    use crate::types::{OpenAIContent, OpenAIMessage};
+
+    fn request(temperature: Option<f32>) -> OpenAIRequest {
+        OpenAIRequest {
+            model: "default".to_string(),
+            messages: vec![OpenAIMessage {
+                role: "user".to_string(),
+                content: OpenAIContent::Text("What is 2 + 2?".to_string()),
+            }],
+            stream: true,
+            max_tokens: 256,
+            temperature,
+            top_p: None,
+            stop: None,
+            stream_options: None,
+            cache_prompt: Some(true),
+            extra: Map::new(),
+        }
+    }
+
+    #[test]
+    fn only_temperature_zero_is_cached() {
+        assert!(!is_deterministic(&request(None)));
+        assert!(!is_deterministic(&request(Some(0.7))));
+        assert!(is_deterministic(&request(Some(0.0))));
+    }
+
+    #[test]
+    fn key_depends_on_model_and_sampling_but_not_stream() {
+        let base = request(Some(0.0));
+        let key = response_cache_key(Some("qwen"), &base).unwrap();
+
+        assert_ne!(key, response_cache_key(Some("llama"), &base).unwrap());
+        assert_ne!(key, response_cache_key(None, &base).unwrap());
+
+        let mut top_p = request(Some(0.0));
+        top_p.top_p = Some(0.5);
+        assert_ne!(key, response_cache_key(Some("qwen"), &top_p).unwrap());
+
+        let mut max_tokens = request(Some(0.0));
+        max_tokens.max_tokens = 512;
+        assert_ne!(key, response_cache_key(Some("qwen"), &max_tokens).unwrap());
+
+        let mut extra = request(Some(0.0));
+        extra.extra.insert("min_p".to_string(), serde_json::json!(0.05));
+        assert_ne!(key, response_cache_key(Some("qwen"), &extra).unwrap());
+
+        let mut not_streamed = request(Some(0.0));
+        not_streamed.stream = false;
+        assert_eq!(key, response_cache_key(Some("qwen"), &not_streamed).unwrap());
+    }
+}
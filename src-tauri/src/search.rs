@@ -0,0 +1,136 @@
+// src-tauri/src/search.rs
+
+/// A single search term, either a bare word or a quoted phrase.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchTerm {
+    pub text: String,
+}
+
+/// A parsed message search query.
+///
+/// Supported syntax:
+/// - `word` matches messages containing the word
+/// - `"exact phrase"` matches the words in that exact order
+/// - `-word` / `-"some phrase"` excludes messages containing it
+#[derive(Clone, Debug, Default)]
+pub struct SearchQuery {
+    pub include: Vec<SearchTerm>,
+    pub exclude: Vec<SearchTerm>,
+}
+
+pub fn parse_search_query(input: &str) -> Result<SearchQuery, String> {
+    let mut query = SearchQuery::default();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let negated = c == '-';
+        if negated {
+            chars.next();
+        }
+
+        let raw = if chars.peek() == Some(&'"') {
+            chars.next();
+            let mut text = String::new();
+            let mut closed = false;
+            for ch in chars.by_ref() {
+                if ch == '"' {
+                    closed = true;
+                    break;
+                }
+                text.push(ch);
+            }
+            if !closed {
+                return Err("Unterminated quote in search query".to_string());
+            }
+            text
+        } else {
+            let mut text = String::new();
+            while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace() {
+                    break;
+                }
+                text.push(ch);
+                chars.next();
+            }
+            text
+        };
+
+        let text = sanitize_term(&raw);
+        if text.is_empty() {
+            // Lone "-", empty quotes or pure punctuation: nothing to match on
+            continue;
+        }
+
+        let term = SearchTerm { text };
+        if negated {
+            query.exclude.push(term);
+        } else {
+            query.include.push(term);
+        }
+    }
+
+    if query.include.is_empty() {
+        if query.exclude.is_empty() {
+            return Err("Search query is empty".to_string());
+        }
+        return Err("Search query needs at least one term that is not excluded".to_string());
+    }
+
+    Ok(query)
+}
+
+/// Markers `snippet()` wraps matches in before they are turned into `<mark>`.
+/// Control characters can't appear in a search term, so they can't collide
+/// with the query, and are unlikely in message text.
+pub const MATCH_START: &str = "\u{1}";
+pub const MATCH_END: &str = "\u{2}";
+
+impl SearchQuery {
+    /// Build an FTS5 `MATCH` expression. Every term is quoted as a phrase, so
+    /// FTS syntax typed by the user (`OR`, `*`, `:`) is matched literally.
+    pub fn to_fts_match(&self) -> String {
+        let quote = |t: &SearchTerm| format!("\"{}\"", t.text.replace('"', "\"\""));
+
+        let mut expr = self.include.iter().map(quote).collect::<Vec<_>>().join(" ");
+        for term in &self.exclude {
+            expr.push_str(" NOT ");
+            expr.push_str(&quote(term));
+        }
+        expr
+    }
+}
+
+/// HTML-escape an FTS snippet and turn its match markers into `<mark>` tags,
+/// so the frontend can render it directly.
+pub fn highlight_snippet(snippet: &str) -> String {
+    snippet
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace(MATCH_START, "<mark>")
+        .replace(MATCH_END, "</mark>")
+}
+
+// ───────────────── private helpers ─────────────────
+
+/// Collapse whitespace and drop control characters. Terms with no
+/// alphanumeric content are discarded since they can't match anything.
+fn sanitize_term(raw: &str) -> String {
+    let cleaned = raw
+        .split_whitespace()
+        .map(|w| w.chars().filter(|c| !c.is_control()).collect::<String>())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if cleaned.chars().any(|c| c.is_alphanumeric()) {
+        cleaned
+    } else {
+        String::new()
+    }
+}
@@ -1,24 +1,544 @@
 // src-tauri/src/server.rs
 
-use std::time::Duration;
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+use std::time::{Duration, SystemTime};
 
-pub async fn wait_for_server_ready(url: &str, timeout_secs: u64) -> Result<(), String> {
+use regex::Regex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::process::{CommandEvent, TerminatedPayload};
+use tauri_plugin_shell::ShellExt;
+
+use crate::db::unix_ms;
+use crate::events::{
+    MODEL_BACKEND, MODEL_CRASHED, MODEL_ERROR, MODEL_LOADING, MODEL_OOM, MODEL_READY,
+    SERVER_LOG_LINE,
+};
+use crate::state::{LlamaServerManager, SERVER_LOG_CAPACITY};
+use crate::types::{AccelerationInfo, ModelCrashedPayload, ModelOomPayload, ServerLogLine};
+
+/// How many ports after the configured one are tried when it is taken.
+const PORT_PROBE_RANGE: u16 = 20;
+
+/// Error returned by `wait_for_server_ready` when `cancel` was set.
+pub const STARTUP_CANCELLED: &str = "Server startup cancelled";
+
+/// Poll `/health` until the server is up, `timeout_secs` pass, or `cancel`
+/// is set (checked between polls).
+pub async fn wait_for_server_ready(
+    url: &str,
+    timeout_secs: u64,
+    cancel: Option<&AtomicBool>,
+) -> Result<(), String> {
     let client = reqwest::Client::new();
     let health_url = format!("{}/health", url);
     let start = std::time::Instant::now();
 
     loop {
+        if cancel.map_or(false, |c| c.load(Ordering::SeqCst)) {
+            return Err(STARTUP_CANCELLED.to_string());
+        }
         if start.elapsed().as_secs() > timeout_secs {
             return Err("Server startup timeout".to_string());
         }
 
-        match client.get(&health_url).send().await {
-            Ok(resp) if resp.status().is_success() => {
-                return Ok(());
+        if is_healthy(&client, &health_url).await {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// Spawn llama-server for `model_path` with the current settings, store the
+/// child in `state.process` and forward its output to the log. Returns the
+/// spawn time for `with_stderr_tail`; does not wait for the server to be ready.
+pub fn spawn_llama_server(
+    app: &AppHandle,
+    state: &LlamaServerManager,
+    model_path: &Path,
+    mmproj_path: Option<&Path>,
+) -> Result<i64, String> {
+    let mut cmd = app.shell().sidecar("llama-server").map_err(|e| e.to_string())?;
+
+    // Context length, max tokens and GPU offload from settings
+    let (server_args, configured_port) = {
+        let settings = state.app_settings.lock().map_err(|e| e.to_string())?;
+        (settings.behavior.server_args(), settings.behavior.server_port)
+    };
+
+    let port = find_free_port(configured_port)?;
+    if port != configured_port {
+        println!("[llama-server] Port {} is in use, using {}", configured_port, port);
+    }
+    state.server_port.store(port, Ordering::SeqCst);
+
+    cmd = cmd
+        .args(["-m", &model_path.to_string_lossy()])
+        .args(["--host", "127.0.0.1"])
+        .args(["--port", &port.to_string()])
+        .args(server_args);
+
+    // Add vision projector if available
+    if let Some(mmproj) = mmproj_path {
+        cmd = cmd.args(["--mmproj", &mmproj.to_string_lossy()]);
+    }
+
+    // Output of a previous process must not be attributed to this one
+    if let Ok(mut oom) = state.oom_line.lock() {
+        *oom = None;
+    }
+    if let Ok(mut acceleration) = state.acceleration.lock() {
+        *acceleration = None;
+    }
+
+    let spawned_at = unix_ms();
+    let (mut rx, child) = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn llama-server: {}", e))?;
+    *state.process.lock().map_err(|e| e.to_string())? = Some(child);
+    *state.server_started_at.lock().map_err(|e| e.to_string())? = Some(SystemTime::now());
+    let generation = state.server_generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    // Log server output in background
+    let log_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => record_server_output(&log_app, "stdout", &line),
+                CommandEvent::Stderr(line) => record_server_output(&log_app, "stderr", &line),
+                CommandEvent::Terminated(payload) => {
+                    report_oom_if_detected(&log_app);
+                    handle_server_exit(&log_app, generation, spawned_at, payload);
+                }
+                CommandEvent::Error(err) => {
+                    eprintln!("[llama-server] {}", err);
+                    let _ = log_app.emit(MODEL_ERROR, err);
+                }
+                _ => {}
+            }
+        }
+    });
+
+    Ok(spawned_at)
+}
+
+/// Kill the running llama-server, if any, and mark it not ready. Returns
+/// whether a process was running.
+pub fn stop_llama_server(state: &LlamaServerManager) -> Result<bool, String> {
+    let child = state.process.lock().map_err(|e| e.to_string())?.take();
+    state.server_generation.fetch_add(1, Ordering::SeqCst);
+    *state.server_started_at.lock().map_err(|e| e.to_string())? = None;
+    state.is_ready.store(false, Ordering::SeqCst);
+    match child {
+        Some(child) => {
+            let _ = child.kill();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Automatic restarts after consecutive crashes before giving up.
+const MAX_CRASH_RESTARTS: u32 = 3;
+/// A server that ran at least this long before crashing starts a fresh
+/// series of restart attempts.
+const STABLE_UPTIME_MS: i64 = 60_000;
+
+/// Handle llama-server exiting. Exits caused by `stop_llama_server` or of a
+/// replaced process are ignored; anything else is a crash, which marks the
+/// server down, emits `model:crashed` and restarts it with backoff.
+fn handle_server_exit(
+    app: &AppHandle,
+    generation: u64,
+    spawned_at: i64,
+    payload: TerminatedPayload,
+) {
+    let state = app.state::<LlamaServerManager>();
+    if state.server_generation.load(Ordering::SeqCst) != generation {
+        return;
+    }
+
+    if let Ok(mut process) = state.process.lock() {
+        process.take();
+    }
+    if let Ok(mut started_at) = state.server_started_at.lock() {
+        *started_at = None;
+    }
+    state.is_ready.store(false, Ordering::SeqCst);
+
+    if unix_ms() - spawned_at >= STABLE_UPTIME_MS {
+        state.crash_count.store(0, Ordering::SeqCst);
+    }
+    let attempt = state.crash_count.fetch_add(1, Ordering::SeqCst) + 1;
+    let restart_attempt = (attempt <= MAX_CRASH_RESTARTS).then_some(attempt);
+
+    eprintln!(
+        "[llama-server] Exited unexpectedly (code {:?}, signal {:?})",
+        payload.code, payload.signal
+    );
+    let _ = app.emit(
+        MODEL_CRASHED,
+        ModelCrashedPayload {
+            code: payload.code,
+            signal: payload.signal,
+            restart_attempt,
+        },
+    );
+
+    if restart_attempt.is_none() {
+        state.crash_count.store(0, Ordering::SeqCst);
+        let _ = app.emit(
+            MODEL_ERROR,
+            format!(
+                "llama-server crashed {} times in a row and was not restarted",
+                attempt
+            ),
+        );
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // 1s, 2s, 4s between attempts
+        tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+
+        let state = app.state::<LlamaServerManager>();
+        // A switch, start or stop since the crash takes precedence
+        if state.server_generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+
+        let model_path = state.model_path.lock().map(|p| p.clone()).unwrap_or_default();
+        let mmproj_path = state.mmproj_path.lock().ok().and_then(|p| p.clone());
+
+        println!(
+            "[llama-server] Restarting after crash (attempt {}/{})",
+            attempt, MAX_CRASH_RESTARTS
+        );
+        let _ = app.emit(MODEL_LOADING, ());
+        match spawn_llama_server(&app, &state, &model_path, mmproj_path.as_deref()) {
+            Ok(spawned_at) => {
+                let _ = await_server_startup(&app, spawned_at).await;
             }
-            _ => {
-                tokio::time::sleep(Duration::from_millis(500)).await;
+            Err(e) => {
+                let _ = app.emit(MODEL_ERROR, e);
             }
         }
+    });
+}
+
+/// `preferred` if nothing listens on it, else the first free port after it.
+fn find_free_port(preferred: u16) -> Result<u16, String> {
+    (preferred..=preferred.saturating_add(PORT_PROBE_RANGE))
+        .find(|&port| TcpListener::bind(("127.0.0.1", port)).is_ok())
+        .ok_or_else(|| {
+            format!(
+                "No free port for llama-server between {} and {}",
+                preferred,
+                preferred.saturating_add(PORT_PROBE_RANGE)
+            )
+        })
+}
+
+/// Wait for a freshly spawned server and report the outcome with
+/// `model:ready` or `model:error`.
+pub async fn await_server_startup(app: &AppHandle, spawned_at: i64) -> Result<(), String> {
+    let state = app.state::<LlamaServerManager>();
+    match wait_for_server_ready(&state.server_url(), 120, None).await {
+        Ok(()) => {
+            state.is_ready.store(true, Ordering::SeqCst);
+            report_cpu_backend_if_undetected(app);
+            let _ = app.emit(MODEL_READY, ());
+            println!("[llama-server] Ready!");
+            Ok(())
+        }
+        Err(e) => {
+            let e = with_stderr_tail(&state, e, spawned_at);
+            let _ = app.emit(MODEL_ERROR, e.clone());
+            Err(e)
+        }
+    }
+}
+
+/// stderr lines appended to a startup failure so the UI shows why the model
+/// didn't load (unknown architecture, OOM, bad file) instead of a bare timeout.
+const STARTUP_ERROR_TAIL_LINES: usize = 8;
+
+/// Append the last stderr lines llama-server printed since `since_ms` to a
+/// startup error.
+pub fn with_stderr_tail(state: &LlamaServerManager, error: String, since_ms: i64) -> String {
+    let tail: Vec<String> = match state.server_log.lock() {
+        Ok(log) => {
+            let mut lines: Vec<String> = log
+                .iter()
+                .rev()
+                .filter(|l| l.stream == "stderr" && l.timestamp >= since_ms)
+                .filter(|l| !l.line.trim().is_empty())
+                .take(STARTUP_ERROR_TAIL_LINES)
+                .map(|l| l.line.clone())
+                .collect();
+            lines.reverse();
+            lines
+        }
+        Err(_) => Vec::new(),
+    };
+
+    if tail.is_empty() {
+        error
+    } else {
+        format!("{}\n\nllama-server output:\n{}", error, tail.join("\n"))
     }
 }
+
+async fn is_healthy(client: &reqwest::Client, health_url: &str) -> bool {
+    matches!(client.get(health_url).send().await, Ok(resp) if resp.status().is_success())
+}
+
+/// Consecutive failed health checks before the server is considered down,
+/// so a single slow response doesn't flip the UI.
+const HEALTH_FAILURES_BEFORE_DOWN: u32 = 2;
+
+/// Periodically hit `/health` while a server process exists and keep
+/// `is_ready` in sync, catching servers that hang without exiting.
+/// Only transitions back to ready if this poller was the one that marked
+/// the server down, so it never races a model switch or startup.
+pub fn spawn_health_poller(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+        {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("[health] Failed to build client: {}", e);
+                return;
+            }
+        };
+        let mut failures: u32 = 0;
+        let mut marked_down = false;
+
+        loop {
+            let state = app.state::<LlamaServerManager>();
+            let interval = state
+                .app_settings
+                .lock()
+                .map(|s| s.behavior.health_check_interval_secs)
+                .unwrap_or(0);
+
+            if interval == 0 {
+                // Disabled; check again later in case the setting changes
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+
+            let has_process = state.process.lock().map(|p| p.is_some()).unwrap_or(false);
+            if !has_process {
+                failures = 0;
+                marked_down = false;
+                continue;
+            }
+
+            let healthy = is_healthy(&client, &format!("{}/health", state.server_url())).await;
+            let ready = state.is_ready.load(Ordering::SeqCst);
+
+            if healthy {
+                failures = 0;
+                if !ready && marked_down {
+                    state.is_ready.store(true, Ordering::SeqCst);
+                    marked_down = false;
+                    println!("[health] Server is responding again");
+                    let _ = app.emit(MODEL_READY, ());
+                }
+            } else if ready {
+                failures += 1;
+                if failures >= HEALTH_FAILURES_BEFORE_DOWN {
+                    state.is_ready.store(false, Ordering::SeqCst);
+                    marked_down = true;
+                    eprintln!("[health] Server stopped responding to health checks");
+                    let _ = app.emit(
+                        MODEL_ERROR,
+                        "llama-server stopped responding to health checks".to_string(),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Lowercased fragments of the messages llama.cpp / its GPU backends print
+/// when an allocation fails.
+const OOM_PATTERNS: &[&str] = &[
+    "out of memory",
+    "failed to allocate",
+    "unable to allocate",
+    "cudamalloc failed",
+    "erroroutofdevicememory",
+    "insufficient memory",
+    "std::bad_alloc",
+];
+
+fn looks_like_oom(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    OOM_PATTERNS.iter().any(|p| lower.contains(p))
+}
+
+/// If llama-server printed an out-of-memory error since the last check, emit
+/// `model:oom` with actionable guidance. Call on a subsequent failure (exit,
+/// broken stream) so the crash is reported as OOM rather than a generic error.
+pub fn report_oom_if_detected(app: &AppHandle) -> bool {
+    let state = app.state::<LlamaServerManager>();
+    let line = match state.oom_line.lock().ok().and_then(|mut l| l.take()) {
+        Some(line) => line,
+        None => return false,
+    };
+
+    eprintln!("[llama-server] Failure after out-of-memory error: {}", line);
+    let _ = app.emit(
+        MODEL_OOM,
+        ModelOomPayload {
+            message: "The model ran out of memory. Try a smaller model or lower the \
+                      context length or GPU layers."
+                .to_string(),
+            log_line: line,
+        },
+    );
+    true
+}
+
+// Device lines checked by `detect_backend`, compiled once rather than for
+// every line of server output
+static USING_DEVICE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"using device ([A-Za-z]+)\d* \(([^)]*)\)").unwrap());
+static CUDA_DEVICE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Device \d+: ([^,]+), compute capability").unwrap());
+static METAL_DEVICE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"ggml_metal_\w*init: (?:GPU name|found device):\s*(.+)").unwrap()
+});
+static VULKAN_DEVICE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"ggml_vulkan: \d+ = ([^(|]+)").unwrap());
+
+/// Which compute backend a line of llama-server startup output reveals, if
+/// any. Covers the device lines printed by the CUDA, Metal and Vulkan
+/// backends and llama.cpp's generic "using device" line.
+fn detect_backend(line: &str) -> Option<AccelerationInfo> {
+    // Cheap pre-check; every pattern below mentions a device or a ggml backend
+    if !line.contains("evice") && !line.contains("ggml_") {
+        return None;
+    }
+
+    let found = |backend: &str, device: &str| {
+        Some(AccelerationInfo {
+            backend: backend.to_string(),
+            device: Some(device.trim().to_string()).filter(|d| !d.is_empty()),
+        })
+    };
+
+    if let Some(caps) = USING_DEVICE.captures(line) {
+        return match caps[1].to_lowercase().as_str() {
+            "cuda" => found("cuda", &caps[2]),
+            "rocm" | "hip" => found("rocm", &caps[2]),
+            "metal" | "mtl" => found("metal", &caps[2]),
+            "vulkan" => found("vulkan", &caps[2]),
+            _ => None,
+        };
+    }
+
+    if let Some(caps) = CUDA_DEVICE.captures(line) {
+        return found("cuda", &caps[1]);
+    }
+
+    if let Some(caps) = METAL_DEVICE.captures(line) {
+        return found("metal", &caps[1]);
+    }
+
+    if let Some(caps) = VULKAN_DEVICE.captures(line) {
+        return found("vulkan", &caps[1]);
+    }
+
+    None
+}
+
+/// Record the backend revealed by a line of output and emit `model:backend`
+/// when it changes. The first GPU device wins; "offloaded 0/N layers" means
+/// the model runs on the CPU even though a GPU was found.
+fn update_backend(app: &AppHandle, state: &LlamaServerManager, line: &str) {
+    let detected = if line.contains("offloaded 0/") && line.contains("layers to GPU") {
+        Some(AccelerationInfo {
+            backend: "cpu".to_string(),
+            device: None,
+        })
+    } else {
+        detect_backend(line)
+    };
+    let detected = match detected {
+        Some(d) => d,
+        None => return,
+    };
+
+    if let Ok(mut current) = state.acceleration.lock() {
+        let is_cpu = detected.backend == "cpu";
+        if current.is_some() && !is_cpu {
+            return;
+        }
+        if current.as_ref() == Some(&detected) {
+            return;
+        }
+        println!("[llama-server] Backend: {}", detected.backend);
+        *current = Some(detected.clone());
+    }
+    let _ = app.emit(MODEL_BACKEND, detected);
+}
+
+/// Once the server is ready, report the CPU backend if startup printed no
+/// GPU device.
+pub fn report_cpu_backend_if_undetected(app: &AppHandle) {
+    let state = app.state::<LlamaServerManager>();
+    let cpu = AccelerationInfo {
+        backend: "cpu".to_string(),
+        device: None,
+    };
+    if let Ok(mut current) = state.acceleration.lock() {
+        if current.is_some() {
+            return;
+        }
+        *current = Some(cpu.clone());
+    }
+    println!("[llama-server] Backend: cpu");
+    let _ = app.emit(MODEL_BACKEND, cpu);
+}
+
+/// Print a line of llama-server output, keep it in the bounded log buffer
+/// and forward it to any subscribed log viewer.
+pub fn record_server_output(app: &AppHandle, stream: &str, bytes: &[u8]) {
+    let line = String::from_utf8_lossy(bytes).trim_end().to_string();
+    if stream == "stderr" {
+        eprintln!("[llama-server] {}", line);
+    } else {
+        println!("[llama-server] {}", line);
+    }
+
+    let entry = ServerLogLine {
+        stream: stream.to_string(),
+        line,
+        timestamp: unix_ms(),
+    };
+
+    let state = app.state::<LlamaServerManager>();
+    update_backend(app, &state, &entry.line);
+    if looks_like_oom(&entry.line) {
+        if let Ok(mut oom) = state.oom_line.lock() {
+            *oom = Some(entry.line.clone());
+        }
+    }
+    if let Ok(mut log) = state.server_log.lock() {
+        while log.len() >= SERVER_LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(entry.clone());
+    }
+
+    let _ = app.emit(SERVER_LOG_LINE, entry);
+}
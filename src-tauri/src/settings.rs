@@ -1,9 +1,43 @@
 // src-tauri/src/settings.rs
 
-use serde::{Deserialize, Serialize};
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::http::validate_proxy_url;
+
+/// Request fields the app sets itself; `extraParams` may not override them.
+const RESERVED_REQUEST_FIELDS: &[&str] = &[
+    "model",
+    "messages",
+    "stream",
+    "max_tokens",
+    "cache_prompt",
+    "stream_options",
+    "temperature",
+    "top_p",
+    "stop",
+];
+
+pub const DEFAULT_SERVER_PORT: u16 = 8080;
+/// Longest image side sent to vision models by default; larger images are
+/// downscaled, which keeps requests and the database small
+pub const DEFAULT_MAX_IMAGE_DIMENSION: u32 = 1536;
+/// Smallest nonzero `max_image_dimension`; below this images become unreadable
+const MIN_IMAGE_DIMENSION: u32 = 256;
+
+/// `SETTINGS_MIGRATIONS[n]` upgrades a settings object from version `n` to
+/// `n + 1`. Append one whenever a settings change would not load from the
+/// previous JSON (a renamed or reshaped field); `SETTINGS_VERSION` follows.
+const SETTINGS_MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[
+    // 0 -> 1: files written before `version` existed need no changes
+    |_| {},
+];
+
+pub const SETTINGS_VERSION: u32 = SETTINGS_MIGRATIONS.len() as u32;
+
 const DEFAULT_SYSTEM_PROMPT: &str = r#"You are Eigen, a helpful AI assistant.
 
 Rules:
@@ -55,6 +89,19 @@ pub struct BehaviorSettings {
     pub streaming_enabled: bool,
     pub context_length: u32,  // --ctx-size: total context window
     pub max_tokens: u32,      // --n-predict: max tokens per response
+    pub gpu_layers: Option<u32>, // --n-gpu-layers: None leaves it to llama-server, 0 forces CPU
+    pub temperature: Option<f32>, // 0.0-2.0, None uses the model default; 0 is deterministic
+    pub top_p: Option<f32>,       // 0.0-1.0, None uses the model default
+    pub stop: Option<Vec<String>>, // stop sequences
+    pub health_check_interval_secs: u64, // 0 disables the background health poller
+    pub parse_think_tags: bool, // split inline <think>...</think> out of the answer
+    pub include_prior_thinking: bool, // replay stored reasoning of earlier turns in the prompt
+    pub extra_params: Map<String, Value>, // merged into chat requests, e.g. {"min_p": 0.05}
+    pub cleanup_empty_chats_on_startup: bool,
+    pub cache_deterministic_responses: bool, // reuse answers to identical temperature-0 requests
+    pub server_port: u16, // --port: the next free port is used when it's taken
+    pub reasoning_enabled: bool, // let thinking models reason before answering
+    pub max_image_dimension: u32, // longer image sides are downscaled to this; 0 sends images as-is
 }
 
 impl Default for BehaviorSettings {
@@ -64,31 +111,239 @@ impl Default for BehaviorSettings {
             streaming_enabled: true,
             context_length: 8192,
             max_tokens: 4096,
+            gpu_layers: None,
+            temperature: None,
+            top_p: None,
+            stop: None,
+            health_check_interval_secs: 10,
+            parse_think_tags: true,
+            include_prior_thinking: false,
+            extra_params: Map::new(),
+            cleanup_empty_chats_on_startup: false,
+            cache_deterministic_responses: false,
+            server_port: DEFAULT_SERVER_PORT,
+            reasoning_enabled: true,
+            max_image_dimension: DEFAULT_MAX_IMAGE_DIMENSION,
         }
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub http_proxy: Option<String>,   // e.g. "http://proxy.corp:3128"
+    pub https_proxy: Option<String>,
+    pub no_proxy: Option<String>,     // comma-separated hosts, local server is always excluded
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct ModelSettings {
+    pub accepted_licenses: Vec<String>, // model ids whose license the user accepted
+    pub hf_token: Option<String>, // sent to huggingface.co for gated models
+    pub catalog_url: Option<String>, // remote model-catalog.json fetched by refresh_catalog
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 #[serde(default)]
+pub struct DebugSettings {
+    pub record_requests: bool,        // append requests + streamed responses to recording_path
+    pub replay_responses: bool,       // serve responses from recording_path instead of llama-server
+    pub recording_path: Option<String>, // JSONL file
+}
+
+/// Deserialized leniently (see the `Deserialize` impl below): missing or
+/// invalid values fall back to their defaults instead of failing the load.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct AppSettings {
     pub version: u32,
     pub appearance: AppearanceSettings,
     pub defaults: DefaultSettings,
     pub behavior: BehaviorSettings,
+    pub network: NetworkSettings,
+    pub models: ModelSettings,
+    pub debug: DebugSettings,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: SETTINGS_VERSION,
             appearance: AppearanceSettings::default(),
             defaults: DefaultSettings::default(),
             behavior: BehaviorSettings::default(),
+            network: NetworkSettings::default(),
+            models: ModelSettings::default(),
+            debug: DebugSettings::default(),
         }
     }
 }
 
+impl<'de> Deserialize<'de> for AppSettings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut object = match Value::deserialize(deserializer)? {
+            Value::Object(object) => object,
+            _ => return Err(D::Error::custom("settings must be a JSON object")),
+        };
+
+        Ok(Self {
+            version: object
+                .get("version")
+                .and_then(Value::as_u64)
+                .map_or(SETTINGS_VERSION, |v| v as u32),
+            appearance: lenient_section("appearance", object.remove("appearance")),
+            defaults: lenient_section("defaults", object.remove("defaults")),
+            behavior: lenient_section("behavior", object.remove("behavior")),
+            network: lenient_section("network", object.remove("network")),
+            models: lenient_section("models", object.remove("models")),
+            debug: lenient_section("debug", object.remove("debug")),
+        })
+    }
+}
+
+/// Deserialize one settings section field by field: values that parse are
+/// kept and anything missing or invalid falls back to the default, so one
+/// bad value can't reset the user's theme or system prompt.
+fn lenient_section<T>(name: &str, value: Option<Value>) -> T
+where
+    T: DeserializeOwned + Serialize + Default,
+{
+    let fields = match value {
+        Some(Value::Object(fields)) => fields,
+        Some(_) => {
+            eprintln!("[settings] Ignoring invalid {} section", name);
+            return T::default();
+        }
+        None => return T::default(),
+    };
+    let mut merged = match serde_json::to_value(T::default()) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => return T::default(),
+    };
+
+    for (key, field) in fields {
+        let previous = merged.insert(key.clone(), field);
+        if serde_json::from_value::<T>(Value::Object(merged.clone())).is_err() {
+            eprintln!("[settings] Ignoring invalid value for {}.{}", name, key);
+            match previous {
+                Some(previous) => merged.insert(key, previous),
+                None => merged.remove(&key),
+            };
+        }
+    }
+
+    serde_json::from_value(Value::Object(merged)).unwrap_or_default()
+}
+
+/// Check settings for values that would break the app before they are persisted
+pub fn validate_settings(settings: &AppSettings) -> Result<(), String> {
+    for proxy in [&settings.network.http_proxy, &settings.network.https_proxy]
+        .into_iter()
+        .flatten()
+    {
+        if !proxy.trim().is_empty() {
+            validate_proxy_url(proxy.trim())?;
+        }
+    }
+
+    if let Some(url) = settings.models.catalog_url.as_deref().map(str::trim) {
+        if !url.is_empty() {
+            let parsed = reqwest::Url::parse(url)
+                .map_err(|e| format!("Invalid catalog URL {:?}: {}", url, e))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(format!("Catalog URL {:?} must use http or https", url));
+            }
+        }
+    }
+
+    let behavior = &settings.behavior;
+    if let Some(t) = behavior.temperature {
+        if !(0.0..=2.0).contains(&t) {
+            return Err(format!("Temperature must be between 0.0 and 2.0, got {}", t));
+        }
+    }
+    if let Some(p) = behavior.top_p {
+        if !(0.0..=1.0).contains(&p) {
+            return Err(format!("Top P must be between 0.0 and 1.0, got {}", p));
+        }
+    }
+    if behavior.server_port < 1024 {
+        return Err(format!("Server port must be between 1024 and 65535, got {}", behavior.server_port));
+    }
+    if behavior.max_image_dimension != 0 && behavior.max_image_dimension < MIN_IMAGE_DIMENSION {
+        return Err(format!(
+            "Max image dimension must be 0 or at least {} pixels, got {}",
+            MIN_IMAGE_DIMENSION, behavior.max_image_dimension
+        ));
+    }
+    if behavior.stop.iter().flatten().any(|s| s.is_empty()) {
+        return Err("Stop sequences cannot be empty".to_string());
+    }
+
+    for key in settings.behavior.extra_params.keys() {
+        if RESERVED_REQUEST_FIELDS.contains(&key.as_str()) {
+            return Err(format!("Extra request parameter \"{}\" is set by the app and cannot be overridden", key));
+        }
+    }
+
+    let debug = &settings.debug;
+    if (debug.record_requests || debug.replay_responses)
+        && debug.recording_path.as_ref().map_or(true, |p| p.trim().is_empty())
+    {
+        return Err("Recording or replay is enabled but no recording path is set".to_string());
+    }
+
+    Ok(())
+}
+
+impl BehaviorSettings {
+    /// llama-server flags derived from these settings. Changing any of them
+    /// only takes effect once the server is restarted.
+    pub fn server_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--ctx-size".to_string(),
+            self.context_length.to_string(),
+            "--n-predict".to_string(),
+            self.max_tokens.to_string(),
+        ];
+        if let Some(layers) = self.gpu_layers {
+            args.push("--n-gpu-layers".to_string());
+            args.push(layers.to_string());
+        }
+        args
+    }
+
+    /// `extra_params` minus anything that would clobber a reserved field,
+    /// in case the settings file was edited by hand.
+    pub fn request_extra_params(&self) -> Map<String, Value> {
+        self.extra_params
+            .iter()
+            .filter(|(key, _)| !RESERVED_REQUEST_FIELDS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// `request_extra_params` for a chat request. With reasoning disabled,
+    /// models that support thinking get `enable_thinking: false` passed to
+    /// their chat template, so no reasoning is generated at all.
+    pub fn chat_request_params(&self, supports_thinking: bool) -> Map<String, Value> {
+        let mut extra = self.request_extra_params();
+        if supports_thinking && !self.reasoning_enabled {
+            let kwargs = extra
+                .entry("chat_template_kwargs")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(kwargs) = kwargs {
+                kwargs.insert("enable_thinking".to_string(), Value::Bool(false));
+            }
+        }
+        extra
+    }
+}
+
 /// Get the path to the settings file (~/.config/eigenAgent/settings.json)
 pub fn get_settings_path() -> Result<PathBuf, String> {
     let config_dir = dirs::config_dir()
@@ -120,13 +375,50 @@ pub fn load_settings() -> Result<AppSettings, String> {
     let content = fs::read_to_string(&path)
         .map_err(|e| format!("Failed to read settings: {}", e))?;
 
-    let settings: AppSettings = serde_json::from_str(&content)
+    let mut value: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse settings: {}", e))?;
+    let migrated = match value.as_object_mut() {
+        Some(object) => migrate_settings(object),
+        None => return Err("Failed to parse settings: not a JSON object".to_string()),
+    };
+
+    let settings: AppSettings = serde_json::from_value(value)
         .map_err(|e| format!("Failed to parse settings: {}", e))?;
 
+    if migrated {
+        save_settings(&settings)?;
+    }
+
     println!("[settings] Loaded settings from {}", path.display());
     Ok(settings)
 }
 
+/// Bring a settings object from its stored `version` up to
+/// `SETTINGS_VERSION`. Returns whether anything changed, so the upgraded
+/// file can be written back. Files from a newer build are left alone.
+fn migrate_settings(settings: &mut Map<String, Value>) -> bool {
+    let stored = settings
+        .get("version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+    if stored >= SETTINGS_VERSION {
+        if stored > SETTINGS_VERSION {
+            eprintln!(
+                "[settings] settings.json is version {}, newer than this build ({})",
+                stored, SETTINGS_VERSION
+            );
+        }
+        return false;
+    }
+
+    for (version, migrate) in SETTINGS_MIGRATIONS.iter().enumerate().skip(stored as usize) {
+        migrate(settings);
+        println!("[settings] Migrated settings from version {} to {}", version, version + 1);
+    }
+    settings.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+    true
+}
+
 /// Save settings to disk
 pub fn save_settings(settings: &AppSettings) -> Result<(), String> {
     let path = get_settings_path()?;
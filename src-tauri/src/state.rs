@@ -1,30 +1,115 @@
 // src-tauri/src/state.rs
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 use std::sync::{
-    atomic::AtomicBool,
+    atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, Ordering},
     Arc, Mutex,
 };
+use std::time::SystemTime;
 
+use reqwest::Client;
 use tauri_plugin_shell::process::CommandChild;
 
 use crate::settings::AppSettings;
+use crate::types::{AccelerationInfo, ModelCapabilities, ServerLogLine};
 
 pub const MAX_TOKENS: u32 = 8192;
-pub const SERVER_PORT: u16 = 8080;
+/// Number of llama-server output lines kept in memory for the log viewer.
+pub const SERVER_LOG_CAPACITY: usize = 500;
+
+/// Stop flags for an in-flight download. Cancel discards the partial files,
+/// pause keeps them so `resume_download` can continue from the same offset.
+/// Also carries live byte counters so `list_downloads` can report progress
+/// without going through events.
+#[derive(Default)]
+pub struct DownloadControl {
+    pub cancelled: AtomicBool,
+    pub paused: AtomicBool,
+    pub downloaded_bytes: AtomicU64,
+    pub total_bytes: AtomicU64,
+    pub speed_bps: AtomicU64,
+    /// Files of this model still downloading or being checked
+    pub files_left: AtomicU32,
+    /// Files whose size and checksum are being checked after their bytes arrived
+    pub verifying: AtomicU32,
+}
+
+impl DownloadControl {
+    pub fn should_stop(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst) || self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Every file still in flight has finished downloading and is being checked.
+    pub fn is_verifying(&self) -> bool {
+        let files_left = self.files_left.load(Ordering::SeqCst);
+        files_left > 0 && self.verifying.load(Ordering::SeqCst) == files_left
+    }
+}
 
 pub struct LlamaServerManager {
     pub process: Mutex<Option<CommandChild>>,
-    pub server_url: String,
+    /// When the running process was spawned, for reporting uptime
+    pub server_started_at: Mutex<Option<SystemTime>>,
+    /// Port llama-server listens on; may differ from the configured one
+    /// when that was taken
+    pub server_port: AtomicU16,
     pub is_ready: AtomicBool,
-    pub is_cancelled: AtomicBool,
+    /// Cancel token per chat with a reply being generated; a second request
+    /// for one of these chats is rejected
+    pub generations: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    /// Set by `cancel_switch` to abort waiting for a model switch
+    pub switch_cancelled: AtomicBool,
+    /// Set while `restart_server_with` is switching models
+    pub switch_in_progress: AtomicBool,
+    /// Bumped on every spawn and stop, so an exit of the current process can
+    /// be told apart from an intentional kill or a replaced server
+    pub server_generation: AtomicU64,
+    /// Consecutive crashes that happened soon after startup
+    pub crash_count: AtomicU32,
+    /// Chats whose in-flight generation should be kept as a draft when cancelled
+    pub draft_requests: Mutex<HashSet<String>>,
+    /// Latest pending live title edit per chat, used to debounce renames
+    pub rename_generations: Mutex<HashMap<String, u64>>,
+    /// Source of rename generations; never reused, so an entry can be removed
+    /// without a later rename matching a stale pending write
+    pub rename_counter: AtomicU64,
+    /// Chats with a title generation in flight
+    pub title_generations: Mutex<HashSet<String>>,
     pub db_path: PathBuf,
     pub models_dir: PathBuf,
     pub model_path: Mutex<PathBuf>,
     pub mmproj_path: Mutex<Option<PathBuf>>,
     pub current_model_id: Mutex<Option<String>>,
-    pub active_downloads: Mutex<HashMap<String, Arc<AtomicBool>>>,
-    pub downloading_progress: Mutex<HashMap<String, f32>>,
+    /// Capabilities of `current_model_id`, updated whenever it changes
+    pub current_capabilities: Mutex<Option<ModelCapabilities>>,
+    pub active_downloads: Mutex<HashMap<String, Arc<DownloadControl>>>,
+    /// Percent per active download; None while the total size is unknown
+    pub downloading_progress: Mutex<HashMap<String, Option<f32>>>,
     pub app_settings: Mutex<AppSettings>,
+    pub server_log: Mutex<VecDeque<ServerLogLine>>,
+    /// Last llama-server output line that looked like an out-of-memory error,
+    /// reported (and cleared) when the server subsequently fails
+    pub oom_line: Mutex<Option<String>>,
+    /// Compute backend detected from the running server's startup output
+    pub acceleration: Mutex<Option<AccelerationInfo>>,
+    /// Shared by all outgoing requests; rebuilt when the network settings change
+    pub http_client: Mutex<Client>,
+}
+
+impl LlamaServerManager {
+    pub fn server_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.server_port.load(Ordering::SeqCst))
+    }
+
+    /// Model name sent in OpenAI requests: the loaded model's catalog id, or
+    /// "default" when none is known. llama-server ignores it, but an
+    /// OpenAI-compatible endpoint serving several models routes on it.
+    pub fn served_model_name(&self) -> String {
+        self.current_model_id
+            .lock()
+            .ok()
+            .and_then(|id| id.clone())
+            .unwrap_or_else(|| "default".to_string())
+    }
 }
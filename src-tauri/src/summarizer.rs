@@ -1,9 +1,11 @@
+// src-tauri/src/summarizer.rs
+
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 
 /// Public API
 pub fn summarize(text: &str, max_sentences: usize) -> String {
-    println!("[summarizer] Received text for summarization: {}", text);
+    println!("[summarizer] Summarizing {} chars to {} sentences", text.len(), max_sentences);
     let sentences = split_sentences(text);
     if sentences.len() <= max_sentences {
         return text.to_string();
@@ -18,7 +20,7 @@ pub fn summarize(text: &str, max_sentences: usize) -> String {
         .map(|(i, s)| (i, score_sentence(s, &word_freq, &stopwords)))
         .collect();
 
-    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
 
     let mut selected: Vec<usize> = scored
         .iter()
@@ -37,12 +39,25 @@ pub fn summarize(text: &str, max_sentences: usize) -> String {
 
 // ───────────────── private helpers ─────────────────
 
+/// Split after `.`, `!` or `?` followed by whitespace. (The regex crate has
+/// no lookbehind, so this is done by hand.)
 fn split_sentences(text: &str) -> Vec<String> {
-    let re = Regex::new(r"(?<=[.!?])\s+").unwrap();
-    re.split(text)
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect()
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        current.push(c);
+        let at_boundary = matches!(c, '.' | '!' | '?')
+            && chars.peek().map_or(true, |next| next.is_whitespace());
+        if at_boundary {
+            sentences.push(current.trim().to_string());
+            current.clear();
+        }
+    }
+    sentences.push(current.trim().to_string());
+
+    sentences.into_iter().filter(|s| !s.is_empty()).collect()
 }
 
 fn word_frequencies(
@@ -0,0 +1,35 @@
+// src-tauri/src/system.rs
+//
+// Host memory and CPU information, used to warn before picking a model the
+// machine can't hold in RAM.
+
+use sysinfo::System;
+
+use crate::types::SystemInfo;
+
+/// Memory needed beyond the model files: the KV cache for a default-sized
+/// context plus llama-server itself.
+const RUNTIME_OVERHEAD_BYTES: u64 = 1024 * 1024 * 1024;
+
+pub fn system_info() -> SystemInfo {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    sys.refresh_cpu();
+
+    // sysinfo reports 0 where memory can't be read (e.g. some sandboxes)
+    let total = sys.total_memory();
+    let available = sys.available_memory();
+
+    SystemInfo {
+        total_memory_bytes: (total > 0).then_some(total),
+        available_memory_bytes: (available > 0).then_some(available),
+        cpu_cores: sys.cpus().len(),
+        physical_cores: sys.physical_core_count(),
+    }
+}
+
+/// Whether a model of `model_bytes` fits in `available_bytes` of RAM, or
+/// `None` when the available memory is unknown.
+pub fn fits_in_ram(model_bytes: u64, available_bytes: Option<u64>) -> Option<bool> {
+    available_bytes.map(|available| model_bytes.saturating_add(RUNTIME_OVERHEAD_BYTES) <= available)
+}
@@ -0,0 +1,165 @@
+// src-tauri/src/think.rs
+//
+// Splits inline `<think>...</think>` reasoning out of a streamed `content`
+// field, for models that don't use llama-server's `reasoning_content`.
+
+const OPEN_TAG: &str = "<think>";
+const CLOSE_TAG: &str = "</think>";
+
+/// Incremental parser fed one SSE content delta at a time. Tags may be split
+/// across chunks, so a trailing partial tag is held back until the next feed.
+#[derive(Default)]
+pub struct ThinkTagParser {
+    in_think: bool,
+    pending: String,
+    /// Hold back text ahead of the first tag instead of emitting it
+    hold_leading: bool,
+    /// A tag has been seen, so held-back text is no longer ambiguous
+    seen_tag: bool,
+    emitted_content: bool,
+}
+
+impl ThinkTagParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parser for a reasoning model whose chat template may open the think
+    /// block in the prompt. Text ahead of the first tag is held back until a
+    /// tag shows whether it was reasoning or the answer.
+    pub fn expecting_reasoning() -> Self {
+        Self {
+            hold_leading: true,
+            ..Self::default()
+        }
+    }
+
+    /// Feed a content delta, returning `(content, thinking)` that can be
+    /// emitted now.
+    pub fn feed(&mut self, chunk: &str) -> (String, String) {
+        self.pending.push_str(chunk);
+
+        let mut content = String::new();
+        let mut thinking = String::new();
+
+        loop {
+            let tag = if self.in_think { CLOSE_TAG } else { OPEN_TAG };
+
+            // A `</think>` outside a block is left over from a chat template
+            // that opened the block in the prompt, so what comes before it is
+            // reasoning unless part of the answer has already gone out
+            if !self.in_think {
+                if let Some(pos) = self.pending.find(CLOSE_TAG) {
+                    if self.pending.find(OPEN_TAG).map_or(true, |open| pos < open) {
+                        if self.emitted_content || !content.is_empty() {
+                            content.push_str(&self.pending[..pos]);
+                        } else {
+                            thinking.push_str(&self.pending[..pos]);
+                        }
+                        self.pending.drain(..pos + CLOSE_TAG.len());
+                        self.seen_tag = true;
+                        continue;
+                    }
+                }
+            }
+
+            let out = if self.in_think { &mut thinking } else { &mut content };
+            if let Some(pos) = self.pending.find(tag) {
+                out.push_str(&self.pending[..pos]);
+                self.pending.drain(..pos + tag.len());
+                self.in_think = !self.in_think;
+                self.seen_tag = true;
+                continue;
+            }
+
+            if self.hold_leading && !self.seen_tag {
+                break;
+            }
+
+            // Keep back any suffix that could be the start of a tag
+            let mut keep = partial_tag_len(&self.pending, tag);
+            if !self.in_think {
+                keep = keep.max(partial_tag_len(&self.pending, CLOSE_TAG));
+            }
+            let emit_to = self.pending.len() - keep;
+            out.push_str(&self.pending[..emit_to]);
+            self.pending.drain(..emit_to);
+            break;
+        }
+
+        self.emitted_content |= !content.is_empty();
+        (content, thinking)
+    }
+
+    /// Flush whatever is still held back once the stream has ended.
+    pub fn finish(&mut self) -> (String, String) {
+        let rest = std::mem::take(&mut self.pending);
+        if self.in_think {
+            (String::new(), rest)
+        } else {
+            (rest, String::new())
+        }
+    }
+}
+
+/// Length of the longest suffix of `text` that is a proper prefix of `tag`.
+fn partial_tag_len(text: &str, tag: &str) -> usize {
+    (1..tag.len())
+        .rev()
+        .find(|&n| text.ends_with(&tag[..n]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `chunks` in order, returning everything emitted before `finish`
+    /// and everything after it.
+    fn run(mut parser: ThinkTagParser, chunks: &[&str]) -> (String, String, String, String) {
+        let (mut content, mut thinking) = (String::new(), String::new());
+        for chunk in chunks {
+            let (c, t) = parser.feed(chunk);
+            content.push_str(&c);
+            thinking.push_str(&t);
+        }
+        let (rest_content, rest_thinking) = parser.finish();
+        (content, thinking, rest_content, rest_thinking)
+    }
+
+    #[test]
+    fn text_before_a_stray_close_tag_is_reasoning() {
+        let (content, thinking, rest, rest_thinking) =
+            run(ThinkTagParser::new(), &["reasoning</think>answer"]);
+        assert_eq!(thinking + &rest_thinking, "reasoning");
+        assert_eq!(content + &rest, "answer");
+    }
+
+    #[test]
+    fn stray_close_tag_split_across_deltas() {
+        let (content, thinking, rest, rest_thinking) = run(
+            ThinkTagParser::expecting_reasoning(),
+            &["reason", "ing</th", "ink>ans", "wer"],
+        );
+        assert_eq!(thinking + &rest_thinking, "reasoning");
+        assert_eq!(content + &rest, "answer");
+    }
+
+    #[test]
+    fn think_block_split_across_deltas() {
+        let (content, thinking, rest, rest_thinking) = run(
+            ThinkTagParser::new(),
+            &["<thi", "nk>reasoning</", "think>answer"],
+        );
+        assert_eq!(thinking + &rest_thinking, "reasoning");
+        assert_eq!(content + &rest, "answer");
+    }
+
+    #[test]
+    fn held_text_without_tags_is_the_answer() {
+        let (content, thinking, rest, rest_thinking) =
+            run(ThinkTagParser::expecting_reasoning(), &["just ", "an answer"]);
+        assert_eq!(content + &rest, "just an answer");
+        assert!(thinking.is_empty() && rest_thinking.is_empty());
+    }
+}
@@ -7,6 +7,8 @@ pub struct ChatMsg {
     pub role: String,
     pub content: String,
     #[serde(default)]
+    pub thinking: String,
+    #[serde(default)]
     pub images: Vec<String>,
 }
 
@@ -16,6 +18,11 @@ pub struct ChatListItem {
     pub title: String,
     pub updated_at: i64,
     pub preview: String,
+    pub archived: bool,
+    pub pinned: bool,
+    pub tags: Vec<String>,
+    /// Model the chat was started with, if one was loaded
+    pub model_id: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -28,6 +35,29 @@ pub struct ChatMessageRow {
     pub created_at: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_tokens: Option<u32>,
+    /// Shared by all regenerated variants of one response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_group_id: Option<String>,
+    pub variant_index: i64,
+    pub variant_count: i64,
+    /// Whether this variant is the one included in future context
+    pub is_active: bool,
+    /// The stream failed before the reply was complete
+    pub truncated: bool,
+}
+
+/// One page of a chat's messages, oldest first.
+#[derive(Serialize)]
+pub struct ChatMessagesPage {
+    pub messages: Vec<ChatMessageRow>,
+    /// Whether messages older than the first one in this page exist
+    pub has_more: bool,
+    /// Model the chat was started with, if one was loaded
+    pub model_id: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -39,6 +69,116 @@ pub struct ChatStreamArgs {
     pub images: Vec<String>,
 }
 
+#[derive(Deserialize)]
+pub struct ChatOnceArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub images: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WarmPromptArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct RegenerateResponseArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct CancelGenerationArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct EditMessageArgs {
+    #[serde(alias = "message_id", alias = "messageId")]
+    pub message_id: String,
+    pub content: String,
+}
+
+#[derive(Deserialize)]
+pub struct SelectVariantArgs {
+    #[serde(alias = "message_id", alias = "messageId")]
+    pub message_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct NewChatArgs {
+    #[serde(default)]
+    pub deferred: bool,
+}
+
+#[derive(Deserialize)]
+pub struct DraftArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+}
+
+/// A partial response kept for editing instead of being committed as a message
+#[derive(Clone, Serialize)]
+pub struct ChatDraft {
+    pub chat_id: String,
+    pub content: String,
+    pub thinking: String,
+    pub updated_at: i64,
+}
+
+/// A message row as stored in a snapshot; `images` stays as the raw JSON column.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SnapshotMessage {
+    pub id: String,
+    pub role: String,
+    pub content: String,
+    pub thinking: String,
+    pub images: String,
+    pub created_at: i64,
+    pub duration_ms: Option<i64>,
+    #[serde(default)]
+    pub prompt_tokens: Option<u32>,
+    #[serde(default)]
+    pub completion_tokens: Option<u32>,
+    pub response_group_id: Option<String>,
+    pub variant_index: i64,
+    pub is_active: bool,
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ChatSnapshotInfo {
+    pub id: String,
+    pub chat_id: String,
+    pub label: String,
+    pub message_count: usize,
+    pub created_at: i64,
+}
+
+#[derive(Deserialize)]
+pub struct SnapshotChatArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+    pub label: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ListSnapshotsArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct SnapshotArgs {
+    #[serde(alias = "snapshot_id", alias = "snapshotId")]
+    pub snapshot_id: String,
+}
+
 #[derive(Deserialize)]
 pub struct RenameChatArgs {
     #[serde(alias = "chat_id", alias = "chatId")]
@@ -52,6 +192,58 @@ pub struct DeleteChatArgs {
     pub chat_id: String,
 }
 
+#[derive(Deserialize, Default)]
+pub struct ListChatsArgs {
+    #[serde(default, alias = "include_archived", alias = "includeArchived")]
+    pub include_archived: bool,
+    /// Only chats carrying this tag
+    #[serde(default, alias = "tag_filter", alias = "tagFilter")]
+    pub tag_filter: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ArchiveChatArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct PinChatArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct ChatSystemPromptArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct SetChatSystemPromptArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+    /// None or blank clears the override
+    #[serde(default, alias = "system_prompt", alias = "systemPrompt")]
+    pub system_prompt: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ChatTagArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+    pub tag: String,
+}
+
+#[derive(Deserialize)]
+pub struct DuplicateChatArgs {
+    #[serde(alias = "chat_id", alias = "chatId")]
+    pub chat_id: String,
+    /// Last message to copy; the whole chat when omitted
+    #[serde(default, alias = "up_to_message_id", alias = "upToMessageId")]
+    pub up_to_message_id: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct GenerateTitleArgs {
     #[serde(alias = "chat_id", alias = "chatId")]
@@ -74,4 +266,30 @@ pub struct ChatDeltaPayload {
 pub struct ChatEndPayload {
     pub chat_id: String,
     pub duration_ms: i64,
+    /// None when the server didn't report usage (or the reply came from cache)
+    pub prompt_tokens: Option<u32>,
+    pub completion_tokens: Option<u32>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ChatErrorPayload {
+    pub chat_id: String,
+    pub error: String,
+}
+
+#[derive(Deserialize)]
+pub struct SearchMessagesArgs {
+    pub query: String,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct MessageSearchResult {
+    pub chat_id: String,
+    pub chat_title: String,
+    pub message_id: String,
+    pub role: String,
+    pub snippet: String,
+    pub created_at: i64,
 }
@@ -0,0 +1,29 @@
+// src-tauri/src/types/database.rs
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize)]
+pub struct DbFlushResult {
+    pub db_bytes: u64,
+    pub wal_bytes: u64,
+    /// The checkpoint could not complete because another connection was busy
+    pub busy: bool,
+}
+
+#[derive(Deserialize)]
+pub struct DatabasePathArgs {
+    pub path: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DbBackupResult {
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DbVacuumResult {
+    /// Database plus WAL file size before and after compacting
+    pub before_bytes: u64,
+    pub after_bytes: u64,
+}
@@ -1,9 +1,13 @@
 // src-tauri/src/types/mod.rs
 
 pub mod chat;
+pub mod database;
 pub mod model;
 pub mod openai;
+pub mod server;
 
 pub use chat::*;
+pub use database::*;
 pub use model::*;
 pub use openai::*;
+pub use server::*;
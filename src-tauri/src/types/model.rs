@@ -6,6 +6,9 @@ use serde::{Deserialize, Serialize};
 pub struct ModelCapabilities {
     pub vision: bool,
     pub thinking: bool,
+    /// Supports OpenAI-style tool calls; absent in older catalogs
+    #[serde(default)]
+    pub tools: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -13,15 +16,38 @@ pub struct ModelFile {
     pub filename: String,
     pub url: String,
     pub size_bytes: u64,
+    /// Lowercase hex SHA-256; downloads are verified against it when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ModelFiles {
+    /// The model file, or the first shard (`*-00001-of-0000N.gguf`) of a split model
     pub model: ModelFile,
+    /// Remaining shards of a split model; llama-server loads them from `model`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shards: Vec<ModelFile>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mmproj: Option<ModelFile>,
 }
 
+impl ModelFiles {
+    /// Every file that must be present on disk for the model to be usable.
+    pub fn all(&self) -> Vec<&ModelFile> {
+        let mut files = vec![&self.model];
+        files.extend(self.shards.iter());
+        if let Some(ref mmproj) = self.mmproj {
+            files.push(mmproj);
+        }
+        files
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.all().iter().map(|f| f.size_bytes).sum()
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ModelCatalogEntry {
     pub id: String,
@@ -29,9 +55,24 @@ pub struct ModelCatalogEntry {
     pub description: String,
     pub size_label: String,
     pub capabilities: ModelCapabilities,
+    /// License name or SPDX id; downloads require accepting it first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
     pub files: ModelFiles,
 }
 
+/// A reason a catalog update can't be applied safely.
+#[derive(Clone, Serialize)]
+pub struct CatalogConflict {
+    pub model_id: String,
+    pub kind: String, // "duplicate_id" | "file_mismatch"
+    pub detail: String,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ModelCatalog {
     pub version: u32,
@@ -45,9 +86,65 @@ pub struct ModelInfo {
     pub description: String,
     pub size_label: String,
     pub capabilities: ModelCapabilities,
-    pub download_status: String, // "not_downloaded" | "downloading" | "downloaded"
+    pub download_status: String, // "not_downloaded" | "downloading" | "paused" | "interrupted" | "downloaded"
     pub download_percent: Option<f32>,
     pub is_current: bool,
+    pub license: Option<String>,
+    pub source_url: Option<String>,
+    pub author: Option<String>,
+    pub license_accepted: bool,
+    /// Whether the model fits in available RAM; None when RAM is unknown
+    pub fits_in_ram: Option<bool>,
+}
+
+/// Everything shown before a download: the model info plus its files.
+#[derive(Clone, Serialize)]
+pub struct ModelDetails {
+    #[serde(flatten)]
+    pub info: ModelInfo,
+    pub files: Vec<ModelFile>,
+    pub total_bytes: u64,
+}
+
+/// A catalog file as it currently exists in the model directory.
+#[derive(Clone, Serialize)]
+pub struct LocalFileState {
+    pub filename: String,
+    pub expected_bytes: u64,
+    pub expected_sha256: Option<String>,
+    pub size_bytes: Option<u64>, // None when the file is missing
+    pub partial_bytes: Option<u64>, // size of a leftover .part file
+    pub sha256: Option<String>,  // only computed with `include_hashes`
+}
+
+#[derive(Clone, Serialize)]
+pub struct LocalModelState {
+    pub model_id: String,
+    pub model_dir: String,
+    pub files: Vec<LocalFileState>,
+    pub problems: Vec<String>,
+}
+
+/// Diagnostic snapshot of the catalog and what is on disk, for bug reports.
+#[derive(Clone, Serialize)]
+pub struct ModelStateExport {
+    pub exported_at: i64,
+    pub models_dir: String,
+    pub current_model_id: Option<String>,
+    pub catalog: ModelCatalog,
+    pub models: Vec<ModelInfo>,
+    pub local: Vec<LocalModelState>,
+    pub downloads: Vec<DownloadInfo>,
+}
+
+/// Catalog models bucketed by capability. A model appears in every bucket it
+/// qualifies for; `text_only` holds models with none of the capabilities.
+#[derive(Clone, Serialize, Default)]
+pub struct ModelsByCapability {
+    pub vision: Vec<ModelInfo>,
+    pub thinking: Vec<ModelInfo>,
+    pub tools: Vec<ModelInfo>,
+    pub text_only: Vec<ModelInfo>,
 }
 
 #[derive(Clone, Serialize)]
@@ -55,18 +152,129 @@ pub struct DownloadProgressPayload {
     pub model_id: String,
     pub downloaded_bytes: u64,
     pub total_bytes: u64,
-    pub percent: f32,
+    /// None when no file size is known; show `downloaded_bytes` instead
+    pub percent: Option<f32>,
+    pub speed_bps: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DownloadInfo {
+    pub model_id: String,
+    pub name: String,
+    pub state: String, // "downloading" | "verifying" | "pausing" | "paused" | "interrupted"
+    pub downloaded_bytes: u64,
+    pub total_bytes: u64,
     pub speed_bps: u64,
+    pub eta_secs: Option<u64>,
+}
+
+/// Emitted when a file download continues from an existing `.part` file.
+#[derive(Clone, Serialize)]
+pub struct DownloadResumedPayload {
+    pub model_id: String,
+    pub filename: String,
+    pub offset_bytes: u64,
+}
+
+/// Emitted once every file of a model is on disk.
+#[derive(Clone, Serialize)]
+pub struct DownloadCompletePayload {
+    pub model_id: String,
+    pub total_bytes: u64,
+    pub elapsed_ms: u64,
+}
+
+/// Emitted when a download fails for good, so the UI can update even if the
+/// `download_model` call that started it is no longer awaited.
+#[derive(Clone, Serialize)]
+pub struct DownloadErrorPayload {
+    pub model_id: String,
+    pub error: String,
+}
+
+/// Emitted before a failed file download is retried.
+#[derive(Clone, Serialize)]
+pub struct DownloadRetryPayload {
+    pub model_id: String,
+    pub filename: String,
+    pub attempt: u32, // the attempt about to start, 2..=max_attempts
+    pub max_attempts: u32,
+    pub error: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ModelVerifyResult {
+    pub model_id: String,
+    pub status: String, // "ok" | "corrupt"
+    pub problems: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct VerifyProgressPayload {
+    pub model_id: String,
+    pub index: usize,
+    pub total: usize,
+    pub status: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DownloadEstimate {
+    pub model_id: String,
+    pub total_bytes: u64,
+    pub speed_bps: u64,
+    pub estimated_secs: Option<u64>, // None when no throughput could be measured
+    pub range_supported: bool,
 }
 
 #[derive(Clone, Serialize)]
 pub struct ModelSwitchPayload {
     pub model_id: String,
-    pub status: String, // "stopping" | "starting" | "ready" | "error"
+    pub status: String, // "stopping" | "starting" | "ready" | "error" | "cancelled"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A chat is continued with a different model than the one it started with.
+#[derive(Clone, Serialize)]
+pub struct ModelMismatchPayload {
+    pub chat_id: String,
+    pub chat_model_id: String,
+    pub loaded_model_id: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CompareProgressPayload {
+    pub model_id: String,
+    pub status: String, // "loading" | "generating" | "done" | "error"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One model's answer in a `compare_models` run. `latency_ms` covers the
+/// request only; time spent loading the model is in `load_ms`.
+#[derive(Clone, Serialize)]
+pub struct ModelComparison {
+    pub model_id: String,
+    pub content: String,
+    pub thinking: String,
+    pub load_ms: u64,
+    pub latency_ms: u64,
+    pub prompt_tokens: Option<u32>,
+    pub completion_tokens: Option<u32>,
+    pub tokens_per_second: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct CompareModelsArgs {
+    pub prompt: String,
+    #[serde(alias = "model_a", alias = "modelA")]
+    pub model_a: String,
+    #[serde(alias = "model_b", alias = "modelB")]
+    pub model_b: String,
+}
+
 #[derive(Deserialize)]
 pub struct SwitchModelArgs {
     #[serde(alias = "model_id", alias = "modelId")]
@@ -90,3 +298,42 @@ pub struct DeleteModelArgs {
     #[serde(alias = "model_id", alias = "modelId")]
     pub model_id: String,
 }
+
+#[derive(Deserialize)]
+pub struct ModelDetailsArgs {
+    #[serde(alias = "model_id", alias = "modelId")]
+    pub model_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct AcceptLicenseArgs {
+    #[serde(alias = "model_id", alias = "modelId")]
+    pub model_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct ImportModelArgs {
+    #[serde(alias = "source_path", alias = "sourcePath")]
+    pub source_path: String,
+    pub name: String,
+    #[serde(default, alias = "mmproj_path", alias = "mmprojPath")]
+    pub mmproj_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CheckCatalogConflictsArgs {
+    pub catalog: ModelCatalog,
+}
+
+#[derive(Deserialize, Default)]
+pub struct ExportModelStateArgs {
+    /// Hash every downloaded file; slow for multi-GB models
+    #[serde(default, alias = "include_hashes", alias = "includeHashes")]
+    pub include_hashes: bool,
+}
+
+#[derive(Deserialize)]
+pub struct EstimateDownloadArgs {
+    #[serde(alias = "model_id", alias = "modelId")]
+    pub model_id: String,
+}
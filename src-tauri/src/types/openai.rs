@@ -1,6 +1,7 @@
 // src-tauri/src/types/openai.rs
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Serialize)]
 pub struct OpenAIRequest {
@@ -8,6 +9,26 @@ pub struct OpenAIRequest {
     pub messages: Vec<OpenAIMessage>,
     pub stream: bool,
     pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<OpenAIStreamOptions>,
+    /// llama-server extension: reuse the KV cache for a matching prompt prefix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_prompt: Option<bool>,
+    /// Extra sampler fields passed through verbatim (`behavior.extraParams`)
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Clone, Copy)]
+pub struct OpenAIStreamOptions {
+    /// Ask for a final chunk carrying token usage
+    pub include_usage: bool,
 }
 
 #[derive(Serialize, Clone)]
@@ -39,7 +60,11 @@ pub struct ImageUrlData {
 
 #[derive(Deserialize, Debug)]
 pub struct OpenAIStreamResponse {
+    #[serde(default)]
     pub choices: Vec<OpenAIStreamChoice>,
+    /// Only on the final chunk, when `include_usage` was requested
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -56,6 +81,14 @@ pub struct OpenAIDelta {
 #[derive(Deserialize, Debug)]
 pub struct OpenAINonStreamResponse {
     pub choices: Vec<OpenAINonStreamChoice>,
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct OpenAIUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
 }
 
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,57 @@
+// src-tauri/src/types/server.rs
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize)]
+pub struct ServerLogLine {
+    pub stream: String, // "stdout" | "stderr"
+    pub line: String,
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ModelOomPayload {
+    pub message: String,
+    pub log_line: String, // the llama-server output that reported the failure
+}
+
+/// The compute backend llama-server is running on.
+#[derive(Clone, Serialize, PartialEq)]
+pub struct AccelerationInfo {
+    pub backend: String, // "cuda" | "rocm" | "metal" | "vulkan" | "cpu"
+    pub device: Option<String>, // e.g. "NVIDIA GeForce RTX 4090"; None on CPU
+}
+
+#[derive(Clone, Serialize)]
+pub struct SystemInfo {
+    pub total_memory_bytes: Option<u64>, // None when it can't be determined
+    pub available_memory_bytes: Option<u64>,
+    pub cpu_cores: usize, // logical cores
+    pub physical_cores: Option<usize>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ModelCrashedPayload {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    /// Restart attempt about to be made; None when giving up
+    pub restart_attempt: Option<u32>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ServerStatus {
+    pub is_ready: bool,
+    pub current_model_id: Option<String>,
+    /// Whether the current model accepts image attachments
+    pub supports_vision: bool,
+    pub download_active: bool,
+    pub server_url: String,
+    /// Seconds since the running process was spawned; None when stopped
+    pub uptime_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct GetServerLogArgs {
+    #[serde(default)]
+    pub lines: Option<usize>,
+}
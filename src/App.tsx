@@ -8,7 +8,7 @@ import "katex/dist/katex.min.css";
 import "./App.css";
 
 // Types
-import { ChatBeginPayload, ChatDeltaPayload, ChatEndPayload, DRAFT_CHAT_ID } from "./types/chat";
+import { ChatBeginPayload, ChatDeltaPayload, ChatEndPayload, ChatErrorPayload, DRAFT_CHAT_ID } from "./types/chat";
 
 // Stores
 import { useSettingsStore } from "./stores/settingsStore";
@@ -52,6 +52,7 @@ export default function App() {
         beginStreaming,
         appendDelta,
         endStreaming,
+        markInterrupted,
         handleStop,
     } = useChatStore();
 
@@ -62,12 +63,14 @@ export default function App() {
         modelReady,
         modelError,
         modelSwitching,
+        visionSupported,
         noModelInstalled,
         initialCheckDone,
         downloadProgress,
         switchModel,
         downloadModel,
         cancelDownload,
+        pauseDownload,
         deleteModel,
     } = useModels(isGenerating);
 
@@ -110,6 +113,7 @@ export default function App() {
         let unlistenBegin: null | (() => void) = null;
         let unlistenDelta: null | (() => void) = null;
         let unlistenEnd: null | (() => void) = null;
+        let unlistenError: null | (() => void) = null;
 
         (async () => {
             unlistenBegin = await listen<ChatBeginPayload>("chat:begin", (event) => {
@@ -130,6 +134,15 @@ export default function App() {
                 appendDelta(content_delta, reasoning_delta);
             });
 
+            unlistenError = await listen<ChatErrorPayload>("chat:error", (event) => {
+                if (!mounted) return;
+                if (!event.payload) return;
+                console.error("[chat:error]", event.payload.error);
+                if (event.payload.chat_id !== activeChatIdRef.current) return;
+
+                markInterrupted();
+            });
+
             unlistenEnd = await listen<ChatEndPayload>("chat:end", (event) => {
                 if (!mounted) return;
                 if (!event.payload) return;
@@ -164,8 +177,9 @@ export default function App() {
             unlistenBegin?.();
             unlistenDelta?.();
             unlistenEnd?.();
+            unlistenError?.();
         };
-    }, [chatId, beginStreaming, appendDelta, endStreaming, setPendingTitleChatId, refreshChats]);
+    }, [chatId, beginStreaming, appendDelta, endStreaming, markInterrupted, setPendingTitleChatId, refreshChats]);
 
     // Load chat list initially + whenever backend says it changed
     useEffect(() => {
@@ -349,6 +363,7 @@ export default function App() {
                 onSwitchModel={switchModel}
                 onDownloadModel={downloadModel}
                 onCancelDownload={cancelDownload}
+                onPauseDownload={pauseDownload}
                 onDeleteModel={deleteModel}
                 onOpenSettings={() => setSettingsOpen(true)}
             />
@@ -363,6 +378,7 @@ export default function App() {
                 pendingFiles={pendingFiles}
                 isGenerating={isGenerating}
                 canSend={canSend}
+                imagesSupported={visionSupported}
                 fileInputRef={fileInputRef}
                 onSelectThinking={setSelectedThinkingId}
                 onInputChange={setInput}
@@ -382,6 +398,7 @@ export default function App() {
                 isOpen={settingsOpen}
                 onClose={() => setSettingsOpen(false)}
                 models={models.filter(m => m.download_status === "downloaded").map(m => ({ id: m.id, name: m.name }))}
+                thinkingSupported={models.some(m => m.is_current && m.capabilities.thinking)}
             />
         </div>
     );
@@ -8,6 +8,7 @@ interface InputAreaProps {
     pendingFiles: FileAttachment[];
     isGenerating: boolean;
     canSend: boolean;
+    imagesSupported: boolean; // the current model accepts image attachments
     fileInputRef: React.RefObject<HTMLInputElement | null>;
     onInputChange: (value: string) => void;
     onKeyDown: (e: React.KeyboardEvent<HTMLInputElement>) => void;
@@ -24,6 +25,7 @@ export function InputArea({
     pendingFiles,
     isGenerating,
     canSend,
+    imagesSupported,
     fileInputRef,
     onInputChange,
     onKeyDown,
@@ -71,7 +73,9 @@ export function InputArea({
                     className="fileUploadBtn"
                     onClick={() => fileInputRef.current?.click()}
                     disabled={isGenerating}
-                    title="Upload file (images, code, documents)"
+                    title={imagesSupported
+                        ? "Upload file (images, code, documents)"
+                        : "Upload file (code, documents); the current model doesn't support images"}
                 >
                     <svg width="20" height="20" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2">
                         <path d="M21.44 11.05l-9.19 9.19a6 6 0 01-8.49-8.49l9.19-9.19a4 4 0 015.66 5.66l-9.2 9.19a2 2 0 01-2.83-2.83l8.49-8.48" />
@@ -80,7 +84,7 @@ export function InputArea({
                 <input
                     type="file"
                     ref={fileInputRef}
-                    accept="image/*,.pdf,.docx,.xlsx,.xls,.txt,.md,.json,.xml,.csv,.tsv,.log,.env,.py,.js,.ts,.tsx,.jsx,.c,.cpp,.h,.hpp,.java,.rb,.go,.rs,.swift,.kt,.scala,.php,.sh,.bash,.zsh,.sql,.r,.lua,.pl,.hs,.ml,.clj,.ex,.exs,.erl,.dart,.vue,.svelte,.html,.htm,.css,.scss,.sass,.less,.yaml,.yml,.toml,.ini,.cfg,.conf,.gitignore,.editorconfig,Dockerfile,Makefile"
+                    accept={(imagesSupported ? "image/*," : "") + ".pdf,.docx,.xlsx,.xls,.txt,.md,.json,.xml,.csv,.tsv,.log,.env,.py,.js,.ts,.tsx,.jsx,.c,.cpp,.h,.hpp,.java,.rb,.go,.rs,.swift,.kt,.scala,.php,.sh,.bash,.zsh,.sql,.r,.lua,.pl,.hs,.ml,.clj,.ex,.exs,.erl,.dart,.vue,.svelte,.html,.htm,.css,.scss,.sass,.less,.yaml,.yml,.toml,.ini,.cfg,.conf,.gitignore,.editorconfig,Dockerfile,Makefile"}
                     multiple
                     onChange={onFileSelect}
                     style={{ display: "none" }}
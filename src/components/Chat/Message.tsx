@@ -110,6 +110,10 @@ export function Message({ message, isSelected, onSelectThinking }: MessageProps)
                                 {(message.durationMs / 1000).toFixed(1)}s
                             </div>
                         )}
+
+                        {message.truncated && (
+                            <div className="truncated">Response interrupted</div>
+                        )}
                     </div>
                 )}
             </div>
@@ -13,6 +13,7 @@ interface ChatProps {
     pendingFiles: FileAttachment[];
     isGenerating: boolean;
     canSend: boolean;
+    imagesSupported: boolean; // the current model accepts image attachments
     fileInputRef: React.RefObject<HTMLInputElement | null>;
     onSelectThinking: (messageId: string) => void;
     onInputChange: (value: string) => void;
@@ -33,6 +34,7 @@ export function Chat({
     pendingFiles,
     isGenerating,
     canSend,
+    imagesSupported,
     fileInputRef,
     onSelectThinking,
     onInputChange,
@@ -66,6 +68,7 @@ export function Chat({
                 pendingFiles={pendingFiles}
                 isGenerating={isGenerating}
                 canSend={canSend}
+                imagesSupported={imagesSupported}
                 fileInputRef={fileInputRef}
                 onInputChange={onInputChange}
                 onKeyDown={onKeyDown}
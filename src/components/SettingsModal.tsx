@@ -1,21 +1,29 @@
 // src/components/SettingsModal.tsx
 
 import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { useSettingsStore } from "../stores/settingsStore";
 import { Theme, FontSize, AppSettings, ACCENT_COLOR_PRESETS, DEFAULT_SYSTEM_PROMPT } from "../types/settings";
+import { DbVacuumResult } from "../types/chat";
+import { formatBytes } from "../utils/format";
 
 interface SettingsModalProps {
     isOpen: boolean;
     onClose: () => void;
     models: Array<{ id: string; name: string }>;
+    thinkingSupported: boolean; // the loaded model can reason before answering
 }
 
-export function SettingsModal({ isOpen, onClose, models }: SettingsModalProps) {
+export function SettingsModal({ isOpen, onClose, models, thinkingSupported }: SettingsModalProps) {
     const { settings, saveSettings, resetSettings } = useSettingsStore();
 
     // Local state for all settings (not persisted until Save)
     const [localSettings, setLocalSettings] = useState<AppSettings>(settings);
     const [hasChanges, setHasChanges] = useState(false);
+    const [vacuumStatus, setVacuumStatus] = useState<string | null>(null);
+    const [vacuuming, setVacuuming] = useState(false);
+    const [catalogStatus, setCatalogStatus] = useState<string | null>(null);
+    const [refreshingCatalog, setRefreshingCatalog] = useState(false);
 
     // Sync local state when modal opens or settings change externally
     useEffect(() => {
@@ -71,6 +79,14 @@ export function SettingsModal({ isOpen, onClose, models }: SettingsModalProps) {
         setHasChanges(true);
     }
 
+    function updateModels(updates: Partial<AppSettings["models"]>) {
+        setLocalSettings(prev => ({
+            ...prev,
+            models: { ...prev.models, ...updates }
+        }));
+        setHasChanges(true);
+    }
+
     async function handleSave() {
         await saveSettings(localSettings);
         setHasChanges(false);
@@ -94,6 +110,34 @@ export function SettingsModal({ isOpen, onClose, models }: SettingsModalProps) {
         }
     }
 
+    async function handleVacuum() {
+        setVacuuming(true);
+        try {
+            const result = await invoke<DbVacuumResult>("vacuum_database");
+            setVacuumStatus(
+                `${formatBytes(result.before_bytes)} → ${formatBytes(result.after_bytes)}`
+            );
+        } catch (e) {
+            console.error("Failed to compact database:", e);
+            setVacuumStatus("Failed to compact database");
+        } finally {
+            setVacuuming(false);
+        }
+    }
+
+    async function handleRefreshCatalog() {
+        setRefreshingCatalog(true);
+        try {
+            const count = await invoke<number>("refresh_catalog");
+            setCatalogStatus(`Updated: ${count} models`);
+        } catch (e) {
+            console.error("Failed to refresh catalog:", e);
+            setCatalogStatus(String(e));
+        } finally {
+            setRefreshingCatalog(false);
+        }
+    }
+
     function handleResetSystemPrompt() {
         updateDefaults({ systemPrompt: DEFAULT_SYSTEM_PROMPT });
     }
@@ -230,6 +274,44 @@ export function SettingsModal({ isOpen, onClose, models }: SettingsModalProps) {
                             </select>
                         </div>
 
+                        <div className="settingRow">
+                            <div className="settingInfo">
+                                <label className="settingLabel">Hugging Face Token</label>
+                                <span className="settingDescription">Needed to download gated models</span>
+                            </div>
+                            <input
+                                type="password"
+                                className="settingInput"
+                                value={localSettings.models.hfToken ?? ""}
+                                onChange={(e) => updateModels({ hfToken: e.target.value || null })}
+                                placeholder="hf_..."
+                                autoComplete="off"
+                            />
+                        </div>
+
+                        <div className="settingRow">
+                            <div className="settingInfo">
+                                <label className="settingLabel">Catalog URL</label>
+                                <span className="settingDescription">
+                                    {catalogStatus ?? "Fetch the model list from this URL (save first)"}
+                                </span>
+                            </div>
+                            <input
+                                type="text"
+                                className="settingInput"
+                                value={localSettings.models.catalogUrl ?? ""}
+                                onChange={(e) => updateModels({ catalogUrl: e.target.value || null })}
+                                placeholder="https://..."
+                            />
+                            <button
+                                className="resetPromptBtn"
+                                onClick={handleRefreshCatalog}
+                                disabled={refreshingCatalog || hasChanges || !settings.models.catalogUrl}
+                            >
+                                {refreshingCatalog ? "Refreshing..." : "Refresh"}
+                            </button>
+                        </div>
+
                         <div className="settingRow vertical">
                             <div className="settingLabelRow">
                                 <label className="settingLabel">System Prompt</label>
@@ -294,6 +376,23 @@ export function SettingsModal({ isOpen, onClose, models }: SettingsModalProps) {
                             </label>
                         </div>
 
+                        {thinkingSupported && (
+                            <div className="settingRow">
+                                <div className="settingInfo">
+                                    <label className="settingLabel">Enable Thinking</label>
+                                    <span className="settingDescription">Let the model reason before answering</span>
+                                </div>
+                                <label className="toggle">
+                                    <input
+                                        type="checkbox"
+                                        checked={localSettings.behavior.reasoningEnabled}
+                                        onChange={(e) => updateBehavior({ reasoningEnabled: e.target.checked })}
+                                    />
+                                    <span className="toggleSlider"></span>
+                                </label>
+                            </div>
+                        )}
+
                         <div className="settingRow vertical">
                             <div className="settingLabelRow">
                                 <label className="settingLabel">Context Length</label>
@@ -336,6 +435,30 @@ export function SettingsModal({ isOpen, onClose, models }: SettingsModalProps) {
                             </div>
                         </div>
                     </section>
+
+                    {/* STORAGE SECTION */}
+                    <section className="settingsSection">
+                        <h3 className="settingsSectionTitle">
+                            <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2">
+                                <ellipse cx="12" cy="5" rx="9" ry="3" />
+                                <path d="M21 12c0 1.66-4 3-9 3s-9-1.34-9-3" />
+                                <path d="M3 5v14c0 1.66 4 3 9 3s9-1.34 9-3V5" />
+                            </svg>
+                            Storage
+                        </h3>
+
+                        <div className="settingRow">
+                            <div className="settingInfo">
+                                <label className="settingLabel">Compact Database</label>
+                                <span className="settingDescription">
+                                    {vacuumStatus ?? "Reclaim disk space left behind by deleted chats"}
+                                </span>
+                            </div>
+                            <button className="resetPromptBtn" onClick={handleVacuum} disabled={vacuuming}>
+                                {vacuuming ? "Compacting..." : "Compact"}
+                            </button>
+                        </div>
+                    </section>
                 </div>
 
                 <div className="settingsFooter">
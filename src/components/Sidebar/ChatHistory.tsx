@@ -26,7 +26,14 @@ export function ChatHistory({ chatHistory, currentChatId, onLoadChat, onDeleteCh
                     title={chat.preview}
                 >
                     <div className="historyItemContent">
-                        <div className="historyTitle">{chat.title}</div>
+                        <div className="historyTitle">
+                            {chat.pinned && (
+                                <svg className="pinIcon" width="12" height="12" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2">
+                                    <path d="M12 17v5M9 3h6l-1 7 4 4H6l4-4-1-7z" />
+                                </svg>
+                            )}
+                            {chat.title}
+                        </div>
                         <div className="historyPreview">{chat.preview}</div>
                         <div className="historyTime">
                             {formatTimestamp(chat.updated_at)}
@@ -5,12 +5,13 @@ import { formatSpeed } from "../../utils/format";
 
 interface ModelCatalogProps {
     models: ModelInfo[];
-    downloadProgress: Record<string, { percent: number; speed: number }>;
+    downloadProgress: Record<string, { percent: number | null; speed: number }>;
     modelSwitching: boolean;
     onClose: () => void;
     onSwitchModel: (modelId: string) => void;
     onDownloadModel: (modelId: string) => void;
     onCancelDownload: (modelId: string) => void;
+    onPauseDownload: (modelId: string) => void;
     onDeleteModel: (modelId: string) => void;
 }
 
@@ -22,6 +23,7 @@ export function ModelCatalog({
     onSwitchModel,
     onDownloadModel,
     onCancelDownload,
+    onPauseDownload,
     onDeleteModel,
 }: ModelCatalogProps) {
     return (
@@ -47,7 +49,7 @@ export function ModelCatalog({
                         return (
                             <div
                                 key={model.id}
-                                className={`modelItem ${isCurrent ? "current" : ""} ${isDownloaded && !isCurrent ? "clickable" : ""}`}
+                                className={`modelItem ${isCurrent ? "current" : ""} ${isDownloaded && !isCurrent ? "clickable" : ""} ${model.fits_in_ram === false ? "tooLarge" : ""}`}
                                 onClick={() => {
                                     if (isDownloaded && !isCurrent && !modelSwitching) {
                                         onSwitchModel(model.id);
@@ -84,6 +86,14 @@ export function ModelCatalog({
                                     </div>
                                 </div>
                                 <div className="modelDescription">{model.description}</div>
+                                {(model.license || model.author) && (
+                                    <div className="modelLicense">
+                                        {[model.author, model.license].filter(Boolean).join(" · ")}
+                                    </div>
+                                )}
+                                {model.fits_in_ram === false && (
+                                    <div className="modelRamWarning">May not fit in available memory</div>
+                                )}
 
                                 {isDownloading && (
                                     <div className="modelDownloadProgress">
@@ -96,6 +106,15 @@ export function ModelCatalog({
                                         <div className="progressInfo">
                                             <span>{(progress?.percent ?? model.download_percent ?? 0).toFixed(1)}%</span>
                                             {progress && <span>{formatSpeed(progress.speed)}</span>}
+                                            <button
+                                                className="cancelDownloadBtn"
+                                                onClick={(e) => {
+                                                    e.stopPropagation();
+                                                    onPauseDownload(model.id);
+                                                }}
+                                            >
+                                                Pause
+                                            </button>
                                             <button
                                                 className="cancelDownloadBtn"
                                                 onClick={(e) => {
@@ -120,7 +139,7 @@ export function ModelCatalog({
                                         <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2">
                                             <path d="M21 15v4a2 2 0 01-2 2H5a2 2 0 01-2-2v-4M7 10l5 5 5-5M12 15V3" />
                                         </svg>
-                                        Download
+                                        {model.download_status === "paused" || model.download_status === "interrupted" ? "Resume" : "Download"}
                                     </button>
                                 )}
 
@@ -14,7 +14,7 @@ interface SidebarProps {
     currentModelName: string;
     noModelInstalled: boolean;
     modelSwitching: boolean;
-    downloadProgress: Record<string, { percent: number; speed: number }>;
+    downloadProgress: Record<string, { percent: number | null; speed: number }>;
     onToggle: () => void;
     onNewChat: () => void;
     onLoadChat: (chatId: string) => void;
@@ -22,6 +22,7 @@ interface SidebarProps {
     onSwitchModel: (modelId: string) => void;
     onDownloadModel: (modelId: string) => void;
     onCancelDownload: (modelId: string) => void;
+    onPauseDownload: (modelId: string) => void;
     onDeleteModel: (modelId: string) => void;
     onOpenSettings: () => void;
 }
@@ -42,6 +43,7 @@ export function Sidebar({
     onSwitchModel,
     onDownloadModel,
     onCancelDownload,
+    onPauseDownload,
     onDeleteModel,
     onOpenSettings,
 }: SidebarProps) {
@@ -76,6 +78,7 @@ export function Sidebar({
                             onSwitchModel={onSwitchModel}
                             onDownloadModel={onDownloadModel}
                             onCancelDownload={onCancelDownload}
+                            onPauseDownload={onPauseDownload}
                             onDeleteModel={onDeleteModel}
                         />
                     )}
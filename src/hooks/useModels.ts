@@ -3,7 +3,7 @@
 import { useState, useEffect, useMemo, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { ModelInfo, DownloadProgressPayload, ModelSwitchPayload } from "../types/model";
+import { AccelerationInfo, ModelCrashedPayload, ModelInfo, ModelMismatchPayload, DownloadCompletePayload, DownloadErrorPayload, DownloadProgressPayload, DownloadResumedPayload, DownloadRetryPayload, ModelOomPayload, ModelSwitchPayload, ServerStatus } from "../types/model";
 
 interface UseModelsReturn {
     // State
@@ -13,15 +13,19 @@ interface UseModelsReturn {
     modelReady: boolean;
     modelError: string | null;
     modelSwitching: boolean;
+    visionSupported: boolean; // the current model accepts image attachments
+    acceleration: AccelerationInfo | null;
+    modelMismatch: ModelMismatchPayload | null; // last chat continued with another model
     noModelInstalled: boolean;
     initialCheckDone: boolean;
-    downloadProgress: Record<string, { percent: number; speed: number }>;
+    downloadProgress: Record<string, { percent: number | null; speed: number }>;
 
     // Actions
     refreshModels: () => Promise<void>;
     switchModel: (modelId: string) => Promise<void>;
     downloadModel: (modelId: string) => Promise<void>;
     cancelDownload: (modelId: string) => Promise<void>;
+    pauseDownload: (modelId: string) => Promise<void>;
     deleteModel: (modelId: string) => Promise<void>;
 }
 
@@ -31,9 +35,12 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
     const [modelReady, setModelReady] = useState(false);
     const [modelError, setModelError] = useState<string | null>(null);
     const [modelSwitching, setModelSwitching] = useState(false);
+    const [visionSupported, setVisionSupported] = useState(false);
+    const [acceleration, setAcceleration] = useState<AccelerationInfo | null>(null);
+    const [modelMismatch, setModelMismatch] = useState<ModelMismatchPayload | null>(null);
     const [noModelInstalled, setNoModelInstalled] = useState(false);
     const [initialCheckDone, setInitialCheckDone] = useState(false);
-    const [downloadProgress, setDownloadProgress] = useState<Record<string, { percent: number; speed: number }>>({});
+    const [downloadProgress, setDownloadProgress] = useState<Record<string, { percent: number | null; speed: number }>>({});
 
     // Computed value for current model name
     const currentModelName = useMemo(() => {
@@ -49,9 +56,10 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
 
             // Find current model
             const current = modelList.find((m) => m.is_current);
-            if (current) {
-                setCurrentModelId(current.id);
-            }
+            setCurrentModelId(current ? current.id : null);
+
+            const status = await invoke<ServerStatus>("get_status");
+            setVisionSupported(status.supports_vision);
         } catch (e) {
             console.log("[list_models] error", e);
         }
@@ -72,11 +80,21 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
 
     const downloadModel = useCallback(async (modelId: string) => {
         try {
+            const model = models.find((m) => m.id === modelId);
+            if (model && !model.license_accepted) {
+                const accepted = window.confirm(
+                    `${model.name} is distributed under the ${model.license} license` +
+                    (model.source_url ? ` (${model.source_url})` : "") +
+                    ".\n\nAccept the license and download?"
+                );
+                if (!accepted) return;
+                await invoke("accept_model_license", { args: { modelId } });
+            }
             await invoke("download_model", { args: { modelId } });
         } catch (e) {
             console.error("[download_model] error", e);
         }
-    }, []);
+    }, [models]);
 
     const cancelDownload = useCallback(async (modelId: string) => {
         try {
@@ -92,6 +110,14 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
         }
     }, []);
 
+    const pauseDownload = useCallback(async (modelId: string) => {
+        try {
+            await invoke("pause_download", { args: { modelId } });
+        } catch (e) {
+            console.error("[pause_download] error", e);
+        }
+    }, []);
+
     const deleteModel = useCallback(async (modelId: string) => {
         try {
             await invoke("delete_model", { args: { modelId } });
@@ -107,6 +133,11 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
         let unErr: null | (() => void) = null;
         let unLoading: null | (() => void) = null;
         let unNoModel: null | (() => void) = null;
+        let unOom: null | (() => void) = null;
+        let unBackend: null | (() => void) = null;
+        let unStopped: null | (() => void) = null;
+        let unCrashed: null | (() => void) = null;
+        let unMismatch: null | (() => void) = null;
 
         (async () => {
             unLoading = await listen("model:loading", () => {
@@ -125,6 +156,32 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
                 setModelError(e.payload);
             });
 
+            unOom = await listen<ModelOomPayload>("model:oom", (e) => {
+                console.log("[event] model:oom", e.payload.log_line);
+                setModelError(e.payload.message);
+                setModelReady(false);
+            });
+
+            unBackend = await listen<AccelerationInfo>("model:backend", (e) => {
+                console.log("[event] model:backend", e.payload.backend, e.payload.device);
+                setAcceleration(e.payload);
+            });
+
+            unStopped = await listen("model:stopped", () => {
+                console.log("[event] model:stopped");
+                setModelReady(false);
+            });
+
+            unCrashed = await listen<ModelCrashedPayload>("model:crashed", (e) => {
+                console.log("[event] model:crashed", e.payload);
+                setModelReady(false);
+            });
+
+            unMismatch = await listen<ModelMismatchPayload>("model:mismatch", (e) => {
+                console.log("[event] model:mismatch", e.payload);
+                setModelMismatch(e.payload);
+            });
+
             unNoModel = await listen("model:no_model", () => {
                 console.log("[event] model:no_model");
                 setNoModelInstalled(true);
@@ -137,6 +194,11 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
             unReady?.();
             unErr?.();
             unNoModel?.();
+            unOom?.();
+            unBackend?.();
+            unStopped?.();
+            unCrashed?.();
+            unMismatch?.();
         };
     }, []);
 
@@ -144,6 +206,10 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
     useEffect(() => {
         let unProgress: null | (() => void) = null;
         let unComplete: null | (() => void) = null;
+        let unDownloadError: null | (() => void) = null;
+        let unResumed: null | (() => void) = null;
+        let unPaused: null | (() => void) = null;
+        let unRetry: null | (() => void) = null;
         let unSwitching: null | (() => void) = null;
         let unModelsChanged: null | (() => void) = null;
 
@@ -156,9 +222,19 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
                 }));
             });
 
-            unComplete = await listen<string>("download:complete", (e) => {
+            unResumed = await listen<DownloadResumedPayload>("download:resumed", (e) => {
+                const { model_id, filename, offset_bytes } = e.payload;
+                console.log("[event] download:resumed", model_id, filename, offset_bytes);
+            });
+
+            unRetry = await listen<DownloadRetryPayload>("download:retry", (e) => {
+                const { model_id, filename, attempt, max_attempts, error } = e.payload;
+                console.log(`[event] download:retry ${model_id} ${filename} (${attempt}/${max_attempts})`, error);
+            });
+
+            unPaused = await listen<string>("download:paused", (e) => {
                 const modelId = e.payload;
-                console.log("[event] download:complete", modelId);
+                console.log("[event] download:paused", modelId);
                 setDownloadProgress((prev) => {
                     const next = { ...prev };
                     delete next[modelId];
@@ -167,6 +243,28 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
                 refreshModels();
             });
 
+            unComplete = await listen<DownloadCompletePayload>("download:complete", (e) => {
+                const { model_id, total_bytes, elapsed_ms } = e.payload;
+                console.log("[event] download:complete", model_id, total_bytes, elapsed_ms);
+                setDownloadProgress((prev) => {
+                    const next = { ...prev };
+                    delete next[model_id];
+                    return next;
+                });
+                refreshModels();
+            });
+
+            unDownloadError = await listen<DownloadErrorPayload>("download:error", (e) => {
+                const { model_id, error } = e.payload;
+                console.error("[event] download:error", model_id, error);
+                setDownloadProgress((prev) => {
+                    const next = { ...prev };
+                    delete next[model_id];
+                    return next;
+                });
+                refreshModels();
+            });
+
             unSwitching = await listen<ModelSwitchPayload>("model:switching", (e) => {
                 const { model_id, status, error } = e.payload;
                 console.log("[event] model:switching", status, model_id, error);
@@ -193,6 +291,10 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
         return () => {
             unProgress?.();
             unComplete?.();
+            unDownloadError?.();
+            unResumed?.();
+            unPaused?.();
+            unRetry?.();
             unSwitching?.();
             unModelsChanged?.();
         };
@@ -217,6 +319,9 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
                         setNoModelInstalled(true);
                     }
                 }
+
+                const status = await invoke<ServerStatus>("get_status");
+                setVisionSupported(status.supports_vision);
             } catch (e) {
                 console.log("[list_models] error", e);
             } finally {
@@ -266,6 +371,9 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
         modelReady,
         modelError,
         modelSwitching,
+        visionSupported,
+        acceleration,
+        modelMismatch,
         noModelInstalled,
         initialCheckDone,
         downloadProgress,
@@ -273,6 +381,7 @@ export function useModels(isGenerating: boolean): UseModelsReturn {
         switchModel,
         downloadModel,
         cancelDownload,
+        pauseDownload,
         deleteModel,
     };
 }
@@ -5,7 +5,7 @@ import { invoke } from "@tauri-apps/api/core";
 import {
     ChatMessage,
     ChatHistoryItem,
-    ChatMessageRow,
+    ChatMessagesPage,
     DRAFT_CHAT_ID,
 } from "../types/chat";
 import { uid } from "../utils/format";
@@ -52,6 +52,7 @@ interface ChatState {
     beginStreaming: (chatId: string) => void;
     appendDelta: (contentDelta: string, reasoningDelta: string) => void;
     endStreaming: (durationMs: number) => void;
+    markInterrupted: () => void;
 
     // Send/Stop
     handleStop: () => Promise<void>;
@@ -87,9 +88,12 @@ export const useChatStore = create<ChatState>((set, get) => ({
         try {
             set({ chatId });
 
-            const rows = await invoke<ChatMessageRow[]>("get_chat_messages", { chatId });
+            const { messages: rows } = await invoke<ChatMessagesPage>("get_chat_messages", {
+                chatId,
+            });
 
-            const loaded: ChatMessage[] = rows.map((r) => ({
+            // Only the active variant of a regenerated response is shown
+            const loaded: ChatMessage[] = rows.filter((r) => r.is_active).map((r) => ({
                 id: r.id,
                 role: (r.role === "assistant" ? "assistant" : "user") as "user" | "assistant",
                 content: r.content,
@@ -102,6 +106,7 @@ export const useChatStore = create<ChatState>((set, get) => ({
                 files: [],
                 isStreaming: false,
                 durationMs: r.duration_ms,
+                truncated: r.truncated,
             }));
 
             set({
@@ -207,9 +212,20 @@ export const useChatStore = create<ChatState>((set, get) => ({
         }));
     },
 
+    markInterrupted: () => {
+        const assistantId = get().currentAssistantId;
+        if (!assistantId) return;
+
+        set((state) => ({
+            messages: state.messages.map((m) =>
+                m.id === assistantId ? { ...m, truncated: true } : m
+            ),
+        }));
+    },
+
     handleStop: async () => {
         try {
-            await invoke("cancel_generation");
+            await invoke("cancel_generation", { args: { chatId: get().chatId } });
         } catch (err) {
             console.error("[cancel_generation] error", err);
         }
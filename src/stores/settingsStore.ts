@@ -2,6 +2,7 @@
 
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { AppSettings, getDefaultSettings, Theme, FontSize } from "../types/settings";
 
 interface SettingsState {
@@ -150,7 +151,7 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
     loadSettings: async () => {
         set({ isLoading: true, error: null });
         try {
-            const settings = await invoke<AppSettings>("cmd_load_settings");
+            const settings = await invoke<AppSettings>("get_settings");
             set({ settings, isLoading: false });
             applyTheme(settings);
         } catch (e) {
@@ -161,9 +162,9 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
 
     saveSettings: async (settings: AppSettings) => {
         try {
-            await invoke("cmd_save_settings", { newSettings: settings });
-            set({ settings });
-            applyTheme(settings);
+            const saved = await invoke<AppSettings>("update_settings", { newSettings: settings });
+            set({ settings: saved });
+            applyTheme(saved);
         } catch (e) {
             console.error("[settings] Failed to save:", e);
             set({ error: String(e) });
@@ -272,3 +273,12 @@ if (typeof window !== "undefined") {
         }
     });
 }
+
+// Pick up settings changed by the backend (e.g. an accepted model license),
+// so the next save doesn't write stale values back
+if (typeof window !== "undefined") {
+    listen<AppSettings>("settings:changed", (event) => {
+        useSettingsStore.setState({ settings: event.payload });
+        applyTheme(event.payload);
+    });
+}
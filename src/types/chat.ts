@@ -25,6 +25,7 @@ export type ChatMessage = {
     files: FileAttachment[];
     isStreaming: boolean;
     durationMs?: number;
+    truncated?: boolean; // the stream failed before the reply was complete
 };
 
 export type ChatHistoryItem = {
@@ -32,6 +33,10 @@ export type ChatHistoryItem = {
     title: string;
     updated_at: number;
     preview: string;
+    archived: boolean;
+    pinned: boolean;
+    tags: string[];
+    model_id: string | null; // model the chat was started with
 };
 
 export type ChatMessageRow = {
@@ -42,6 +47,20 @@ export type ChatMessageRow = {
     images: string[];
     created_at: number;
     duration_ms?: number;
+    prompt_tokens?: number;
+    completion_tokens?: number;
+    response_group_id?: string;
+    variant_index: number;
+    variant_count: number;
+    is_active: boolean;
+    truncated: boolean;
+};
+
+export type ChatMessagesPage = {
+    messages: ChatMessageRow[];
+    // Whether messages older than the first one in this page exist
+    has_more: boolean;
+    model_id: string | null; // model the chat was started with
 };
 
 export type ChatBeginPayload = {
@@ -57,6 +76,18 @@ export type ChatDeltaPayload = {
 export type ChatEndPayload = {
     chat_id: string;
     duration_ms: number;
+    prompt_tokens: number | null;
+    completion_tokens: number | null;
+};
+
+export type ChatErrorPayload = {
+    chat_id: string;
+    error: string;
 };
 
 export const DRAFT_CHAT_ID = "__draft__";
+
+export type DbVacuumResult = {
+    before_bytes: number;
+    after_bytes: number;
+};
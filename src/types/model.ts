@@ -3,6 +3,7 @@
 export type ModelCapabilities = {
     vision: boolean;
     thinking: boolean;
+    tools: boolean;
 };
 
 export type ModelInfo = {
@@ -11,21 +12,137 @@ export type ModelInfo = {
     description: string;
     size_label: string;
     capabilities: ModelCapabilities;
-    download_status: string; // "not_downloaded" | "downloading" | "downloaded"
+    download_status: string; // "not_downloaded" | "downloading" | "paused" | "interrupted" | "downloaded"
     download_percent: number | null;
     is_current: boolean;
+    license: string | null;
+    source_url: string | null;
+    author: string | null;
+    license_accepted: boolean;
+    fits_in_ram: boolean | null; // null when available RAM is unknown
+};
+
+export type ModelFile = {
+    filename: string;
+    url: string;
+    size_bytes: number;
+};
+
+export type ModelDetails = ModelInfo & {
+    files: ModelFile[];
+    total_bytes: number;
+};
+
+export type ModelsByCapability = {
+    vision: ModelInfo[];
+    thinking: ModelInfo[];
+    tools: ModelInfo[];
+    text_only: ModelInfo[];
 };
 
 export type DownloadProgressPayload = {
     model_id: string;
     downloaded_bytes: number;
     total_bytes: number;
-    percent: number;
+    percent: number | null;
+    speed_bps: number;
+};
+
+export type DownloadInfo = {
+    model_id: string;
+    name: string;
+    // "pausing": pause requested, waiting for the file writes to stop.
+    // "verifying": all bytes are in and the size/checksum is being checked.
+    state: string; // "downloading" | "verifying" | "pausing" | "paused" | "interrupted"
+    downloaded_bytes: number;
+    total_bytes: number;
     speed_bps: number;
+    eta_secs: number | null;
+};
+
+export type DownloadResumedPayload = {
+    model_id: string;
+    filename: string;
+    offset_bytes: number;
+};
+
+export type DownloadCompletePayload = {
+    model_id: string;
+    total_bytes: number;
+    elapsed_ms: number;
+};
+
+export type DownloadErrorPayload = {
+    model_id: string;
+    error: string;
+};
+
+export type DownloadRetryPayload = {
+    model_id: string;
+    filename: string;
+    attempt: number;
+    max_attempts: number;
+    error: string;
+};
+
+export type ModelOomPayload = {
+    message: string;
+    log_line: string;
+};
+
+export type ModelCrashedPayload = {
+    code: number | null;
+    signal: number | null;
+    restart_attempt: number | null; // null when no restart will be attempted
+};
+
+export type ModelMismatchPayload = {
+    chat_id: string;
+    chat_model_id: string;
+    loaded_model_id: string;
+};
+
+export type AccelerationInfo = {
+    backend: string; // "cuda" | "rocm" | "metal" | "vulkan" | "cpu"
+    device: string | null;
 };
 
 export type ModelSwitchPayload = {
     model_id: string;
-    status: string; // "stopping" | "starting" | "ready" | "error"
+    status: string; // "stopping" | "starting" | "ready" | "error" | "cancelled"
     error?: string;
 };
+
+export type CompareProgressPayload = {
+    model_id: string;
+    status: string; // "loading" | "generating" | "done" | "error"
+    error?: string;
+};
+
+export type ModelComparison = {
+    model_id: string;
+    content: string;
+    thinking: string;
+    load_ms: number;
+    latency_ms: number;
+    prompt_tokens: number | null;
+    completion_tokens: number | null;
+    tokens_per_second: number | null;
+    error?: string;
+};
+
+export type SystemInfo = {
+    total_memory_bytes: number | null;
+    available_memory_bytes: number | null;
+    cpu_cores: number; // logical cores
+    physical_cores: number | null;
+};
+
+export type ServerStatus = {
+    is_ready: boolean;
+    current_model_id: string | null;
+    supports_vision: boolean; // the current model accepts image attachments
+    download_active: boolean;
+    server_url: string;
+    uptime_secs: number | null; // null while the server is stopped
+};
@@ -19,6 +19,37 @@ export interface BehaviorSettings {
     streamingEnabled: boolean;
     contextLength: number;  // --ctx-size: total context window
     maxTokens: number;      // --n-predict: max tokens per response
+    gpuLayers: number | null;  // --n-gpu-layers: null leaves it to llama-server, 0 forces CPU
+    temperature: number | null;  // 0.0-2.0, null uses the model default; 0 is deterministic
+    topP: number | null;         // 0.0-1.0, null uses the model default
+    stop: string[] | null;       // stop sequences
+    healthCheckIntervalSecs: number;  // 0 disables the background health poller
+    parseThinkTags: boolean;          // split inline <think>...</think> out of the answer
+    includePriorThinking: boolean;    // replay stored reasoning of earlier turns in the prompt
+    extraParams: Record<string, unknown>;  // merged into chat requests, e.g. { min_p: 0.05 }
+    cleanupEmptyChatsOnStartup: boolean;
+    cacheDeterministicResponses: boolean;  // reuse answers to identical temperature-0 requests
+    serverPort: number;  // --port: the next free port is used when it's taken
+    reasoningEnabled: boolean;  // let thinking models reason before answering
+    maxImageDimension: number;  // longer image sides are downscaled to this; 0 sends images as-is
+}
+
+export interface NetworkSettings {
+    httpProxy: string | null;
+    httpsProxy: string | null;
+    noProxy: string | null;  // comma-separated hosts, local server is always excluded
+}
+
+export interface ModelSettings {
+    acceptedLicenses: string[];     // model ids whose license the user accepted
+    hfToken: string | null;         // sent to huggingface.co for gated models
+    catalogUrl: string | null;      // remote model-catalog.json fetched by refresh_catalog
+}
+
+export interface DebugSettings {
+    recordRequests: boolean;        // append requests + streamed responses to recordingPath
+    replayResponses: boolean;       // serve responses from recordingPath instead of llama-server
+    recordingPath: string | null;   // JSONL file
 }
 
 export interface AppSettings {
@@ -26,6 +57,9 @@ export interface AppSettings {
     appearance: AppearanceSettings;
     defaults: DefaultSettings;
     behavior: BehaviorSettings;
+    network: NetworkSettings;
+    models: ModelSettings;
+    debug: DebugSettings;
 }
 
 export const DEFAULT_SYSTEM_PROMPT = `You are Eigen, a helpful AI assistant.
@@ -60,5 +94,33 @@ export const getDefaultSettings = (): AppSettings => ({
         streamingEnabled: true,
         contextLength: 8192,
         maxTokens: 4096,
+        gpuLayers: null,
+        temperature: null,
+        topP: null,
+        stop: null,
+        healthCheckIntervalSecs: 10,
+        parseThinkTags: true,
+        includePriorThinking: false,
+        extraParams: {},
+        cleanupEmptyChatsOnStartup: false,
+        cacheDeterministicResponses: false,
+        serverPort: 8080,
+        reasoningEnabled: true,
+        maxImageDimension: 1536,
+    },
+    network: {
+        httpProxy: null,
+        httpsProxy: null,
+        noProxy: null,
+    },
+    models: {
+        acceptedLicenses: [],
+        hfToken: null,
+        catalogUrl: null,
+    },
+    debug: {
+        recordRequests: false,
+        replayResponses: false,
+        recordingPath: null,
     },
 });